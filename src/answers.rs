@@ -0,0 +1,58 @@
+use std::{collections::BTreeMap, fs};
+
+use crate::config::Config;
+
+pub const FILE_NAME: &str = "answers.toml";
+
+/// Known answers, read from `answers.toml` in a year's input directory. Each table is named
+/// after an input and has the answer to each part:
+///
+/// ```toml
+/// [12_sample_1]
+/// part1 = "There are 10 valid paths"
+/// part2 = "There are 36 valid paths"
+/// ```
+#[derive(Default)]
+pub struct Answers(BTreeMap<String, BTreeMap<String, String>>);
+
+pub enum Verification {
+    Correct,
+    Wrong { expected: String },
+    Unknown,
+}
+
+impl Answers {
+    pub fn load(config: &Config, year: u16) -> Result<Self, String> {
+        let path = config.year_file(year, FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&text)
+            .map(Self)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, input_name: &str, part: u8) -> Option<&str> {
+        self.0
+            .get(input_name)
+            .and_then(|parts| parts.get(&format!("part{}", part)))
+            .map(String::as_str)
+    }
+
+    /// The names of all inputs with a known answer
+    pub fn inputs(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    pub fn verify(&self, input_name: &str, part: u8, answer: &str) -> Verification {
+        match self.get(input_name, part) {
+            None => Verification::Unknown,
+            Some(expected) if expected == answer => Verification::Correct,
+            Some(expected) => Verification::Wrong {
+                expected: expected.to_string(),
+            },
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use std::{fmt, fs, path::Path};
+
+/// What a single line of an input looks like
+#[derive(Clone, Copy)]
+enum Line {
+    /// A non-negative integer
+    Integer,
+    /// `forward 5`, `down 3` or `up 1`
+    Command,
+    /// A string of 0s and 1s, as long as the first line of its section
+    Binary,
+    /// Comma-separated non-negative integers
+    CommaSeparated,
+    /// This many whitespace-separated non-negative integers
+    Row(usize),
+    /// `x,y -> x,y`
+    Segment,
+    /// A string of `()[]{}<>`
+    Brackets,
+    /// A string of digits, as long as the first line of its section
+    Digits,
+    /// `a-b` between two caves
+    Edge,
+    /// `x,y`
+    Point,
+    /// `fold along x=5` or `fold along y=7`
+    Fold,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Integer => write!(f, "an integer"),
+            Line::Command => write!(f, "a command like 'forward 5'"),
+            Line::Binary => write!(f, "a binary number as long as the first one"),
+            Line::CommaSeparated => write!(f, "comma-separated integers"),
+            Line::Row(n) => write!(f, "a row of {} integers", n),
+            Line::Segment => write!(f, "a line segment like '0,9 -> 5,9'"),
+            Line::Brackets => write!(f, "a string of brackets"),
+            Line::Digits => write!(f, "a row of digits as long as the first one"),
+            Line::Edge => write!(f, "a connection like 'start-A'"),
+            Line::Point => write!(f, "a point like '6,10'"),
+            Line::Fold => write!(f, "a fold like 'fold along y=7'"),
+        }
+    }
+}
+
+fn is_integer(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_point(text: &str) -> bool {
+    matches!(text.split_once(','), Some((x, y)) if is_integer(x) && is_integer(y))
+}
+
+impl Line {
+    /// Whether `line` looks right. `first` is the first line of the section it is in.
+    fn matches(&self, line: &str, first: &str) -> bool {
+        match self {
+            Line::Integer => is_integer(line),
+            Line::Command => matches!(
+                line.split_once(' '),
+                Some(("forward" | "down" | "up", n)) if is_integer(n)
+            ),
+            Line::Binary => {
+                line.len() == first.len() && line.bytes().all(|b| b == b'0' || b == b'1')
+            }
+            Line::CommaSeparated => line.split(',').all(is_integer),
+            Line::Row(n) => {
+                let numbers: Vec<&str> = line.split_whitespace().collect();
+                numbers.len() == *n && numbers.iter().all(|n| is_integer(n))
+            }
+            Line::Segment => {
+                matches!(line.split_once(" -> "), Some((a, b)) if is_point(a) && is_point(b))
+            }
+            Line::Brackets => !line.is_empty() && line.bytes().all(|b| b"()[]{}<>".contains(&b)),
+            Line::Digits => line.len() == first.len() && is_integer(line),
+            Line::Edge => matches!(
+                line.split_once('-'),
+                Some((a, b)) if [a, b].iter().all(|cave| {
+                    !cave.is_empty() && cave.bytes().all(|b| b.is_ascii_alphabetic())
+                })
+            ),
+            Line::Point => is_point(line),
+            Line::Fold => matches!(
+                line.strip_prefix("fold along ").and_then(|fold| fold.split_once('=')),
+                Some(("x" | "y", n)) if is_integer(n)
+            ),
+        }
+    }
+}
+
+/// A block of lines, separated from the next block by a blank line
+struct Section {
+    line: Line,
+    /// How many lines the section has, if that is fixed
+    lines: Option<usize>,
+}
+
+impl Section {
+    fn any(line: Line) -> Self {
+        Self { line, lines: None }
+    }
+
+    fn exactly(lines: usize, line: Line) -> Self {
+        Self {
+            line,
+            lines: Some(lines),
+        }
+    }
+}
+
+/// What a day's input looks like
+struct Shape {
+    sections: Vec<Section>,
+    /// Whether the last section can be repeated, e.g. the bingo boards of day 4
+    repeat_last: bool,
+}
+
+impl Shape {
+    fn lines(line: Line) -> Self {
+        Self::sections(vec![Section::any(line)])
+    }
+
+    fn sections(sections: Vec<Section>) -> Self {
+        Self {
+            sections,
+            repeat_last: false,
+        }
+    }
+}
+
+fn shape(year: u16, day: u8) -> Option<Shape> {
+    if year != 2021 {
+        return None;
+    }
+
+    Some(match day {
+        1 => Shape::lines(Line::Integer),
+        2 => Shape::lines(Line::Command),
+        3 => Shape::lines(Line::Binary),
+        4 => Shape {
+            sections: vec![
+                Section::exactly(1, Line::CommaSeparated),
+                Section::exactly(5, Line::Row(5)),
+            ],
+            repeat_last: true,
+        },
+        5 => Shape::lines(Line::Segment),
+        6 | 7 => Shape::sections(vec![Section::exactly(1, Line::CommaSeparated)]),
+        9 | 11 => Shape::lines(Line::Digits),
+        10 => Shape::lines(Line::Brackets),
+        12 => Shape::lines(Line::Edge),
+        13 => Shape::sections(vec![Section::any(Line::Point), Section::any(Line::Fold)]),
+        _ => return None,
+    })
+}
+
+/// The first formatting problem that `normalise` would fix, with its line number
+fn formatting_problem(text: &str) -> Option<(usize, &'static str)> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    for (idx, line) in lines.iter().enumerate() {
+        if line.ends_with('\r') {
+            return Some((idx + 1, "has a Windows (CRLF) line ending"));
+        }
+        if line.ends_with(char::is_whitespace) {
+            return Some((idx + 1, "has trailing whitespace"));
+        }
+    }
+
+    if !text.ends_with('\n') {
+        Some((lines.len(), "has no final newline"))
+    } else if text.ends_with("\n\n") || text == "\n" {
+        let last = text.trim_end_matches('\n').split('\n').count();
+        Some((last + 1, "is one of the blank lines at the end"))
+    } else {
+        None
+    }
+}
+
+/// `text` with LF line endings, no trailing whitespace and exactly one final newline
+fn normalise(text: &str) -> String {
+    let mut normalised = text
+        .split('\n')
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end_matches('\n')
+        .to_string();
+    normalised.push('\n');
+    normalised
+}
+
+/// Check the lines of an input that has been normalised against `shape`
+fn check_shape(text: &str, shape: &Shape) -> Result<(), String> {
+    // Sections as the line number of their first line and their lines
+    let mut sections: Vec<(usize, Vec<&str>)> = Vec::new();
+    let mut start_section = true;
+    for (idx, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            start_section = true;
+        } else {
+            if start_section {
+                sections.push((idx + 1, Vec::new()));
+                start_section = false;
+            }
+            sections.last_mut().unwrap().1.push(line);
+        }
+    }
+
+    if sections.is_empty() {
+        return Err("the input is empty".to_string());
+    }
+    if sections.len() < shape.sections.len() {
+        return Err(format!(
+            "expected {} blocks separated by blank lines, found {}",
+            shape.sections.len(),
+            sections.len()
+        ));
+    }
+    if sections.len() > shape.sections.len() && !shape.repeat_last {
+        return Err(format!(
+            "line {}: expected the input to end before this block",
+            sections[shape.sections.len()].0
+        ));
+    }
+
+    for (idx, (start, lines)) in sections.iter().enumerate() {
+        let section = shape
+            .sections
+            .get(idx)
+            .unwrap_or_else(|| shape.sections.last().unwrap());
+
+        for (line_idx, line) in lines.iter().enumerate() {
+            if section.lines == Some(line_idx) {
+                return Err(format!(
+                    "line {}: expected a blank line after {} lines, found '{}'",
+                    start + line_idx,
+                    line_idx,
+                    line
+                ));
+            }
+            if !section.line.matches(line, lines[0]) {
+                return Err(format!(
+                    "line {}: expected {}, found '{}'",
+                    start + line_idx,
+                    section.line,
+                    line
+                ));
+            }
+        }
+
+        if let Some(expected) = section.lines.filter(|expected| lines.len() < *expected) {
+            return Err(format!(
+                "line {}: expected a block of {} lines, found {}",
+                start + lines.len() - 1,
+                expected,
+                lines.len()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that the input at `path` looks like an input for `day` of `year`. With `fix`, formatting
+/// problems like CRLF line endings are fixed in place first.
+pub fn check_input(year: u16, day: u8, path: &Path, fix: bool) -> Result<(), String> {
+    let shape = shape(year, day)
+        .ok_or_else(|| format!("I don't know what inputs for day {} look like", day))?;
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let text = match formatting_problem(&text) {
+        None => text,
+        Some(_) if fix => {
+            let normalised = normalise(&text);
+            fs::write(path, &normalised).map_err(|e| format!("{}: {}", path.display(), e))?;
+            println!("Normalised {}", path.display());
+            normalised
+        }
+        Some((line, problem)) => {
+            return Err(format!(
+                "{}: line {} {}, --fix normalises the file",
+                path.display(),
+                line,
+                problem
+            ))
+        }
+    };
+
+    check_shape(&text, &shape).map_err(|e| format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::registry::DEFAULT_YEAR;
+
+pub const FILE_NAME: &str = "aoc21.toml";
+
+/// Where the value of a setting came from
+#[derive(Debug, Clone)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Cli,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    pub fn new(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+
+    /// Replace the value, if there is a new one
+    pub fn update(&mut self, value: Option<T>, source: Source) {
+        if let Some(value) = value {
+            *self = Self::new(value, source);
+        }
+    }
+}
+
+/// Settings for one extra. Anything left unset falls back to the extra's own default.
+#[derive(Debug, Clone, Default)]
+pub struct ExtraConfig {
+    /// Overrides the app's `WINDOW_FPS`, 0 means as fast as possible
+    pub fps: Option<Setting<u32>>,
+    /// Overrides the extra's `USE_SAMPLE`
+    pub use_sample: Option<Setting<bool>>,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    /// Directory the inputs are read from
+    pub inputs: Setting<PathBuf>,
+    /// The sample used when `-s` is given without a name
+    pub sample: Setting<String>,
+    /// TTF font used by visualisations. If it doesn't exist they use another font on the system,
+    /// or a built-in one.
+    pub font: Setting<PathBuf>,
+    /// Keyed by day and the extra's name
+    extras: BTreeMap<(u8, String), ExtraConfig>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    inputs: Option<PathBuf>,
+    sample: Option<String>,
+    font: Option<PathBuf>,
+    /// `[extras.{day}.{name}]` tables
+    #[serde(default)]
+    extras: BTreeMap<String, BTreeMap<String, ExtraConfigFile>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ExtraConfigFile {
+    fps: Option<u32>,
+    use_sample: Option<bool>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            inputs: Setting::new(PathBuf::from("inputs"), Source::Default),
+            sample: Setting::new("1".to_string(), Source::Default),
+            font: Setting::new(
+                PathBuf::from("/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf"),
+                Source::Default,
+            ),
+            extras: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// The configuration files that are read, in order. Later files override earlier ones, so
+    /// the project's file wins over the user's.
+    pub fn files() -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        if let Some(dir) = dirs::config_dir() {
+            files.push(dir.join(FILE_NAME));
+        }
+        files.push(PathBuf::from(FILE_NAME));
+        files
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let mut config = Self::default();
+        for path in Self::files() {
+            if path.exists() {
+                config.read_file(&path)?;
+            }
+        }
+        Ok(config)
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<(), String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let file: ConfigFile =
+            toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let source = Source::File(path.to_path_buf());
+
+        self.inputs.update(file.inputs, source.clone());
+        self.sample.update(file.sample, source.clone());
+        self.font.update(file.font, source.clone());
+
+        for (day, extras) in file.extras {
+            let day: u8 = day
+                .parse()
+                .map_err(|_| format!("{}: [extras.{}] is not a day", path.display(), day))?;
+            for (name, extra) in extras {
+                let entry = self.extra_mut(day, &name);
+                if let Some(fps) = extra.fps {
+                    entry.fps = Some(Setting::new(fps, source.clone()));
+                }
+                if let Some(use_sample) = extra.use_sample {
+                    entry.use_sample = Some(Setting::new(use_sample, source.clone()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The settings for the extra called `name` of `day`
+    pub fn extra(&self, day: u8, name: &str) -> ExtraConfig {
+        self.extras
+            .get(&(day, name.to_string()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn extra_mut(&mut self, day: u8, name: &str) -> &mut ExtraConfig {
+        self.extras.entry((day, name.to_string())).or_default()
+    }
+
+    /// The name of the input for `day`, or one of its samples. An empty sample name means the
+    /// unnumbered `{day}_sample`.
+    pub fn input_name(day: u8, sample: Option<&str>) -> String {
+        match sample {
+            None => day.to_string(),
+            Some("") => format!("{}_sample", day),
+            Some(sample) => format!("{}_sample_{}", day, sample),
+        }
+    }
+
+    /// The input for `day` of `year`, or one of its samples
+    pub fn input_path(&self, year: u16, day: u8, sample: Option<&str>) -> PathBuf {
+        self.year_file(year, &Self::input_name(day, sample))
+    }
+
+    /// A file in the input directory of `year`. Inputs live in `{inputs}/{year}/`, but files for
+    /// the default year are also found directly in `{inputs}/`, where they were before there
+    /// were years.
+    pub fn year_file(&self, year: u16, name: &str) -> PathBuf {
+        let path = self.year_dir(year).join(name);
+        let flat_path = self.inputs.value.join(name);
+        if year == DEFAULT_YEAR && !path.exists() && flat_path.exists() {
+            flat_path
+        } else {
+            path
+        }
+    }
+
+    /// The directory with the inputs for `year`
+    pub fn year_dir(&self, year: u16) -> PathBuf {
+        self.inputs.value.join(year.to_string())
+    }
+
+    /// Print the effective configuration. `extras` are the day and name of every extra, with
+    /// whether it uses a sample by default.
+    pub fn print(&self, extras: &[(u8, &str, bool)]) {
+        fn line(key: &str, value: impl Debug, source: &Source) {
+            println!("{:<12} {:<60} # {}", key, format!("= {:?}", value), source);
+        }
+
+        line("inputs", &self.inputs.value, &self.inputs.source);
+        line("sample", &self.sample.value, &self.sample.source);
+        line("font", &self.font.value, &self.font.source);
+
+        for (day, name, default_use_sample) in extras {
+            let extra = self.extra(*day, name);
+            println!();
+            println!("[extras.{}.{}]", day, name);
+            match &extra.fps {
+                Some(fps) => line("fps", fps.value, &fps.source),
+                None => println!(
+                    "{:<12} {:<60} # {}",
+                    "fps",
+                    "= (app default)",
+                    Source::Default
+                ),
+            }
+            match &extra.use_sample {
+                Some(use_sample) => line("use_sample", use_sample.value, &use_sample.source),
+                None => line("use_sample", default_use_sample, &Source::Default),
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::params::Params;
+
+/// How much a solver logs. Each level includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// What a solver is doing, a few lines per part
+    Info,
+    /// Every step of a solver, e.g. every bingo draw
+    Debug,
+    /// Whole states after every step, e.g. all bingo boards
+    Trace,
+}
+
+impl Level {
+    /// The level enabled by giving `-v` `count` times, none without it
+    pub fn from_verbosity(count: u64) -> Option<Self> {
+        match count {
+            0 => None,
+            1 => Some(Level::Info),
+            2 => Some(Level::Debug),
+            _ => Some(Level::Trace),
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Info => write!(f, "info"),
+            Level::Debug => write!(f, "debug"),
+            Level::Trace => write!(f, "trace"),
+        }
+    }
+}
+
+/// Everything a solver gets besides its input: a logger, progress reporting and the `-p`
+/// parameters. Logs and progress go to stderr, so they don't get mixed up with the answers. The
+/// default context is silent and has no parameters, which is what tests, fuzzing and reports use.
+#[derive(Debug, Default)]
+pub struct Context {
+    level: Option<Level>,
+    params: Params,
+}
+
+impl Context {
+    pub fn new(level: Option<Level>, params: Params) -> Self {
+        Self { level, params }
+    }
+
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    pub fn enabled(&self, level: Level) -> bool {
+        self.level.is_some_and(|enabled| level <= enabled)
+    }
+
+    /// Log a line at `level`. Taking `format_args!` means nothing is formatted when the level is
+    /// disabled.
+    pub fn log(&self, level: Level, args: fmt::Arguments) {
+        if self.enabled(level) {
+            eprintln!("[{}] {}", level, args);
+        }
+    }
+
+    pub fn info(&self, args: fmt::Arguments) {
+        self.log(Level::Info, args);
+    }
+
+    pub fn debug(&self, args: fmt::Arguments) {
+        self.log(Level::Debug, args);
+    }
+
+    pub fn trace(&self, args: fmt::Arguments) {
+        self.log(Level::Trace, args);
+    }
+
+    /// Call `dump` only when `level` is enabled, for output that is too big for a single line,
+    /// e.g. printing a whole grid
+    pub fn dump(&self, level: Level, dump: impl FnOnce()) {
+        if self.enabled(level) {
+            dump();
+        }
+    }
+
+    /// Report progress through a long loop of `total` steps, or an unknown number of steps.
+    /// Shown from `-v` on.
+    pub fn progress(&self, label: &str, total: Option<usize>) -> Progress {
+        Progress {
+            label: label.to_string(),
+            total,
+            done: 0,
+            shown: self.enabled(Level::Info),
+            last_drawn: None,
+        }
+    }
+}
+
+/// A progress line on stderr, redrawn at most every `REDRAW_INTERVAL` and finished when dropped
+pub struct Progress {
+    label: String,
+    total: Option<usize>,
+    done: usize,
+    shown: bool,
+    last_drawn: Option<Instant>,
+}
+
+impl Progress {
+    const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// One more step done
+    pub fn tick(&mut self) {
+        self.advance(1);
+    }
+
+    pub fn advance(&mut self, steps: usize) {
+        self.done += steps;
+        if self.shown
+            && self
+                .last_drawn
+                .is_none_or(|drawn| drawn.elapsed() >= Self::REDRAW_INTERVAL)
+        {
+            self.draw();
+        }
+    }
+
+    fn draw(&mut self) {
+        match self.total {
+            Some(total) if total > 0 => eprint!(
+                "\r{}: {}/{} ({}%)",
+                self.label,
+                self.done,
+                total,
+                100 * self.done / total
+            ),
+            _ => eprint!("\r{}: {}", self.label, self.done),
+        }
+        io::stderr().flush().ok();
+        self.last_drawn = Some(Instant::now());
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.last_drawn.is_some() {
+            // Show the final count, which may have been skipped to keep redraws down
+            self.draw();
+            eprintln!();
+        }
+    }
+}
@@ -1,12 +1,13 @@
 use std::io::BufRead;
 
-use crate::Solution;
+use crate::{context::Context, Solution};
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 1;
     const PART: u8 = 1;
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut increases = 0;
         let mut lines = buf.lines().map(|l| l.unwrap());
 
@@ -22,16 +23,17 @@ impl Solution for Part1 {
             last = this;
         }
 
-        format!("The value increases {} times", increases)
+        Ok(format!("The value increases {} times", increases))
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 1;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut increases = 0;
         let lines: Vec<i32> = buf.lines().map(|l| l.unwrap().parse().unwrap()).collect();
 
@@ -50,6 +52,9 @@ impl Solution for Part2 {
             last_sum = this_sum;
         }
 
-        format!("The sliding window value increases {} times", increases)
+        Ok(format!(
+            "The sliding window value increases {} times",
+            increases
+        ))
     }
 }
@@ -1,6 +1,6 @@
 use std::io::BufRead;
 
-use crate::Solution;
+use crate::{context::Context, Solution};
 
 fn read_input(buf: &mut impl BufRead) -> Vec<Vec<char>> {
     buf.lines()
@@ -46,10 +46,11 @@ fn score_missing_bracket(c: char) -> u64 {
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 10;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut score: u64 = 0;
         for line in read_input(buf) {
             let mut bracket_stack: Vec<char> = Vec::new();
@@ -66,16 +67,17 @@ impl Solution for Part1 {
                 }
             }
         }
-        format!("The total score is {}", score)
+        Ok(format!("The total score is {}", score))
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 10;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut line_scores: Vec<u64> = Vec::new();
 
         'all_lines: for line in read_input(buf) {
@@ -104,6 +106,6 @@ impl Solution for Part2 {
         line_scores.sort();
         let middle_score = line_scores[line_scores.len() / 2];
 
-        format!("The total score is {}", middle_score)
+        Ok(format!("The total score is {}", middle_score))
     }
 }
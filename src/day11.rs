@@ -1,22 +1,29 @@
 use std::io::BufRead;
 
 use ansi_term::{Colour, Style};
-use sdl2::{pixels::Color, rect::Rect, render::Canvas, video::Window};
-
-use crate::{util::neighbour_indices_with_diag, visualisation::WindowApp, Extra, Solution};
+use sdl2::{
+    pixels::Color,
+    render::{Canvas, RenderTarget},
+};
+
+use crate::{
+    context::{Context, Level},
+    geometry::Aabb,
+    params::Params,
+    parse, util,
+    visualisation::{
+        camera::Camera,
+        simulation::{Animation, Renderer, Simulation},
+        WindowApp, WindowSettings,
+    },
+    Extra, Solution,
+};
 
 // Negative value means the octopus has already flashed
-type Grid = Vec<Vec<i8>>;
+pub type Grid = util::Grid<i8>;
 
 fn read_input(buf: &mut impl BufRead) -> Grid {
-    buf.lines()
-        .map(Result::unwrap)
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).unwrap() as i8)
-                .collect()
-        })
-        .collect()
+    Grid::from_digits(&parse::read_to_string(buf)).unwrap()
 }
 
 fn print_grid(grid: &Grid) {
@@ -25,7 +32,7 @@ fn print_grid(grid: &Grid) {
     let way_over_nine: Style = Style::new().fg(Colour::Red);
     let negative: Style = Style::new().fg(Colour::Red);
 
-    for row in grid {
+    for row in grid.rows() {
         for c in row {
             let style = if *c == 0 {
                 zero
@@ -37,37 +44,33 @@ fn print_grid(grid: &Grid) {
                 normal
             };
 
-            print!("{}", style.paint(format!("{}", *c)));
+            eprint!("{}", style.paint(format!("{}", *c)));
         }
-        println!();
+        eprintln!();
     }
 }
 
-fn step_grid(grid: &mut Grid) -> u32 {
-    let width = grid.len();
-    let height = grid[0].len();
+pub fn step_grid(grid: &mut Grid) -> u32 {
     let mut flashes = 0;
 
-    for row in grid.iter_mut() {
-        for energy in row.iter_mut() {
-            *energy += 1;
-        }
+    for (_, energy) in grid.iter_mut() {
+        *energy += 1;
     }
 
     loop {
         let mut had_a_flash = false;
-        for row in 0..grid.len() {
-            for col in 0..grid[row].len() {
-                if grid[row][col] > 9 {
+        for row in 0..grid.height() {
+            for col in 0..grid.width() {
+                if grid[(row, col)] > 9 {
                     flashes += 1;
-                    grid[row][col] = -1;
+                    grid[(row, col)] = -1;
 
                     had_a_flash = true;
 
-                    for (nrow, ncol) in neighbour_indices_with_diag(row, col, width, height) {
-                        if grid[nrow][ncol] >= 0 {
+                    for neighbour in grid.neighbour_positions_with_diag((row, col)) {
+                        if grid[neighbour] >= 0 {
                             // If it hasn't flashed already
-                            grid[nrow][ncol] += 1
+                            grid[neighbour] += 1
                         }
                     }
                 }
@@ -78,11 +81,9 @@ fn step_grid(grid: &mut Grid) -> u32 {
         }
     }
 
-    for row in grid.iter_mut() {
-        for energy in row.iter_mut() {
-            if *energy < 0 {
-                *energy = 0;
-            }
+    for (_, energy) in grid.iter_mut() {
+        if *energy < 0 {
+            *energy = 0;
         }
     }
 
@@ -91,35 +92,46 @@ fn step_grid(grid: &mut Grid) -> u32 {
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 11;
     const PART: u8 = 1;
+    const PARAMS: &'static [(&'static str, &'static str)] = &[(
+        "steps",
+        "How many steps to count flashes for, 100 by default",
+    )];
 
-    fn solve(buf: &mut impl BufRead) -> String {
-        const STEPS: u32 = 100;
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
+        let steps: u32 = ctx.params().get_or("steps", 100)?;
 
         let mut grid: Grid = read_input(buf);
         let mut flashes = 0;
 
-        for _step in 1..=STEPS {
+        for step in 1..=steps {
             flashes += step_grid(&mut grid);
+            ctx.debug(format_args!("Step {}: {} flashes so far", step, flashes));
+            ctx.dump(Level::Trace, || print_grid(&grid));
         }
 
-        format!("There have been a total of {} flashes", flashes)
+        Ok(format!("There have been a total of {} flashes", flashes))
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 11;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
         let mut grid: Grid = read_input(buf);
-        let octopus_count = (grid.len() * grid[0].len()) as u32;
+        let octopus_count = grid.len() as u32;
 
+        let mut progress = ctx.progress("Steps", None);
         let mut step = 1;
         loop {
             let flashes = step_grid(&mut grid);
+            progress.tick();
+            ctx.dump(Level::Trace, || print_grid(&grid));
 
             if flashes == octopus_count {
                 break;
@@ -128,82 +140,162 @@ impl Solution for Part2 {
             step += 1;
         }
 
-        format!(
+        Ok(format!(
             "The first step when all octopuses flash together is {}",
             step
-        )
+        ))
     }
 }
 
-pub struct Octoblink {
+/// The octopuses flashing step by step, until they all flash together
+pub struct Octopuses {
     initial_grid: Grid,
-    grid: Grid,
-    background_drawn: bool,
 }
 
-impl Extra for Octoblink {
-    const DAY: u8 = 11;
-    const USE_SAMPLE: bool = false;
+impl Octopuses {
+    pub fn new(grid: Grid) -> Self {
+        Self { initial_grid: grid }
+    }
+}
+
+impl Simulation for Octopuses {
+    type State = Grid;
+
+    fn initial(&self) -> Grid {
+        self.initial_grid.clone()
+    }
+
+    fn step(&self, grid: &mut Grid) {
+        step_grid(grid);
+    }
 
-    fn run(buf: &mut impl BufRead) {
-        Self::new(read_input(buf)).run_window();
+    /// Every octopus that flashes ends its step at 0
+    fn finished(&self, grid: &Grid) -> bool {
+        grid.iter().all(|(_, energy)| *energy == 0)
     }
 }
 
-impl Octoblink {
-    fn new(grid: Grid) -> Self {
-        Self {
-            initial_grid: grid.clone(),
-            grid,
-            background_drawn: false,
-        }
+pub struct Octoblink {
+    /// Pixels per octopus to start with, fitted to the window if not given
+    cell_size: Option<u32>,
+}
+
+impl Extra for Octoblink {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 11;
+    const NAME: &'static str = "octoblink";
+    const USE_SAMPLE: bool = false;
+    const PARAMS: &'static [(&'static str, &'static str)] = &[(
+        "cell_size",
+        "Size of an octopus in pixels, fits the grid to the window by default",
+    )];
+
+    fn run(buf: &mut impl BufRead, window: &WindowSettings, params: &Params) -> Result<(), String> {
+        let renderer = Self {
+            cell_size: params.get("cell_size")?,
+        };
+        Animation::new(Octopuses::new(read_input(buf)), renderer).run_window(window);
+        Ok(())
     }
 }
 
-impl WindowApp for Octoblink {
+impl Renderer<Octopuses> for Octoblink {
     const WINDOW_NAME: &'static str = "Day 11 - Octoblink";
     const WINDOW_WIDTH: u32 = 800;
     const WINDOW_HEIGHT: u32 = 800;
-    const WINDOW_FPS: Option<u32> = Some(10);
+    const STEPS_PER_SECOND: f64 = 10.;
 
-    fn reset(&mut self) {
-        self.grid = self.initial_grid.clone();
+    fn content(&self, octopuses: &Octopuses) -> Option<Aabb> {
+        Some(octopuses.initial_grid.bounds())
     }
 
-    fn draw_frame(&mut self, canvas: &mut Canvas<Window>) -> Result<bool, String> {
-        let size: u32 = 80;
+    fn zoom(&self) -> Option<f64> {
+        self.cell_size.map(f64::from)
+    }
 
+    fn draw<T: RenderTarget>(
+        &mut self,
+        _octopuses: &Octopuses,
+        grid: &Grid,
+        canvas: &mut Canvas<T>,
+        camera: &Camera,
+    ) -> Result<(), String> {
         canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
 
         canvas.set_draw_color(Color::RGB(0x00, 0x00, 0x33));
         canvas.clear();
 
-        let _flash_count = step_grid(&mut self.grid);
-
-        for (row_idx, row) in self.grid.iter().enumerate() {
-            for (col_idx, energy) in row.iter().enumerate() {
-                let color = Color::RGBA(
-                    0xFF,
-                    0x00,
-                    0xFF,
-                    if *energy == 0 {
-                        0xFF
-                    } else {
-                        6 * (*energy as u8)
-                    },
-                );
-                canvas.set_draw_color(color);
-                let rect = Rect::new(
-                    size as i32 * col_idx as i32,
-                    size as i32 * row_idx as i32,
-                    size,
-                    size,
-                );
-
-                canvas.fill_rect(rect)?;
-            }
+        for (position, energy) in grid.iter() {
+            let color = Color::RGBA(
+                0xFF,
+                0x00,
+                0xFF,
+                if *energy == 0 {
+                    0xFF
+                } else {
+                    6 * (*energy as u8)
+                },
+            );
+            canvas.set_draw_color(color);
+            canvas.fill_rect(camera.cell(position))?;
         }
 
-        Ok(true)
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::visualisation::{Frame, Headless};
+
+    const SAMPLE: &str = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+";
+
+    const FLASH: Color = Color::RGB(0xFF, 0x00, 0xFF);
+
+    fn pixels(frame: &Frame) -> impl Iterator<Item = Color> + '_ {
+        (0..frame.height).flat_map(move |y| (0..frame.width).map(move |x| frame.pixel(x, y)))
+    }
+
+    // SDL crashes the whole test binary rather than failing where it can't render, so this only
+    // runs when asked for with `cargo test -- --ignored`
+    #[test]
+    #[ignore = "needs an SDL that can render, run with --ignored"]
+    fn octoblink_draws_until_all_flash_together() {
+        let mut app = Animation::new(
+            Octopuses::new(read_input(&mut SAMPLE.as_bytes())),
+            Octoblink { cell_size: None },
+        );
+        // The grid fits the canvas with 10 pixels per octopus, and every frame is a step
+        let mut frames: Vec<Frame> = Vec::new();
+        let drawn = Headless::new(100, 100)
+            .unwrap()
+            .run(&mut app, 1000, Duration::from_millis(100), |frame| {
+                frames.push(frame.clone());
+                Ok(())
+            })
+            .unwrap();
+
+        // The start and the 195 steps until they all flash
+        assert_eq!(drawn, 196);
+        assert_eq!(frames.len(), drawn);
+
+        assert!(pixels(&frames[0]).all(|pixel| pixel != FLASH));
+        // In step 2 the octopus in row 0, column 2 flashes and the one in column 0 doesn't
+        assert_ne!(frames[2].pixel(5, 5), FLASH);
+        assert_eq!(frames[2].pixel(25, 5), FLASH);
+        assert!(pixels(&frames[195]).all(|pixel| pixel == FLASH));
     }
 }
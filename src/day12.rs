@@ -3,11 +3,14 @@ use std::{
     io::BufRead,
 };
 
-use crate::Solution;
+use crate::{
+    context::Context,
+    util::graph::{self, Visit, VisitPolicy},
+    Solution,
+};
 
 type Node = String;
 type Map = HashMap<String, Vec<Node>>;
-type Path<'a> = Vec<&'a str>;
 
 fn read_input(buf: &mut impl BufRead, map: &mut Map) {
     buf.lines()
@@ -27,106 +30,97 @@ fn read_input(buf: &mut impl BufRead, map: &mut Map) {
         });
 }
 
-fn find_end<'a, 'b>(
-    map: &'a Map,
-    start: &'a str,
-    small_visited: &mut HashSet<&'a str>,
-) -> Vec<Path<'a>> {
-    if small_visited.contains(start) {
-        return vec![];
-    }
-
-    if start == "end" {
-        return vec![vec!["end"]];
-    }
-
-    if start.chars().next().unwrap().is_lowercase() {
-        small_visited.insert(start);
-    }
-
-    let mut paths: Vec<Path> = map[start]
-        .iter()
-        .flat_map(|next| find_end(map, next.as_str(), small_visited))
-        .collect();
-
-    for path in paths.iter_mut() {
-        path.insert(0, start)
-    }
-
-    small_visited.remove(start);
+fn is_small(cave: &str) -> bool {
+    cave.chars().next().unwrap().is_lowercase()
+}
 
+/// The number of paths from start to end where `policy` decides which caves can be entered
+fn count_paths<'a>(map: &'a Map, policy: &mut impl VisitPolicy<&'a str>) -> usize {
+    let mut paths = 0;
+    graph::dfs(
+        "start",
+        |cave| map[*cave].iter().map(String::as_str),
+        policy,
+        |path| {
+            if path.last() == Some(&"end") {
+                paths += 1;
+                Visit::Prune
+            } else {
+                Visit::Continue
+            }
+        },
+    );
     paths
 }
 
-fn find_end_double_visit<'a>(
-    map: &'a Map,
-    start: &'a str,
-    small_visited: &mut HashSet<&'a str>,
-    double_visit_done: bool,
-    start_allowed: bool,
-) -> Vec<Path<'a>> {
-    if !start_allowed && start == "start" {
-        return vec![];
-    } else if start == "end" {
-        return vec![vec!["end"]];
-    } else if small_visited.contains(start) && double_visit_done {
-        return vec![];
+/// Small caves can be visited at most once, and with `twice` a single one of them can be
+/// visited twice, though not the start. The small caves on the current path are counted as the
+/// search enters and leaves them.
+struct SmallCaves<'a> {
+    twice: bool,
+    visited: HashSet<&'a str>,
+    /// The small cave on the path a second time, if any
+    visited_twice: Option<&'a str>,
+}
+
+impl<'a> SmallCaves<'a> {
+    fn new(twice: bool) -> Self {
+        Self {
+            twice,
+            visited: HashSet::new(),
+            visited_twice: None,
+        }
     }
+}
 
-    let second_visit = if start.chars().next().unwrap().is_lowercase() {
-        !small_visited.insert(start) && start != "start"
-    } else {
+impl<'a> VisitPolicy<&'a str> for SmallCaves<'a> {
+    fn may_visit(&mut self, _path: &[&'a str], cave: &&'a str) -> bool {
+        if !is_small(cave) || self.visited.insert(cave) {
+            return true;
+        }
+        if self.twice && self.visited_twice.is_none() && *cave != "start" {
+            self.visited_twice = Some(cave);
+            return true;
+        }
         false
-    };
-
-    let mut paths: Vec<Path> = map[start]
-        .iter()
-        .flat_map(|next| {
-            find_end_double_visit(
-                map,
-                next.as_str(),
-                small_visited,
-                double_visit_done || second_visit,
-                false,
-            )
-        })
-        .collect();
-
-    for path in paths.iter_mut() {
-        path.insert(0, start)
     }
 
-    if !second_visit {
-        small_visited.remove(start);
+    fn leave(&mut self, _path: &[&'a str], cave: &&'a str) {
+        // The second visit is left before the first
+        if self.visited_twice == Some(*cave) {
+            self.visited_twice = None;
+        } else if is_small(cave) {
+            self.visited.remove(cave);
+        }
     }
-
-    paths
 }
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 12;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut map: Map = HashMap::new();
         read_input(buf, &mut map);
-        let paths = find_end(&map, "start", &mut HashSet::new());
+        let paths = count_paths(&map, &mut SmallCaves::new(false));
 
-        format!("There are {} valid paths", paths.len())
+        Ok(format!("There are {} valid paths", paths))
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 12;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut map: Map = HashMap::new();
         read_input(buf, &mut map);
-        let paths = find_end_double_visit(&map, "start", &mut HashSet::new(), false, true);
+        let paths = count_paths(&map, &mut SmallCaves::new(true));
 
-        format!("There are {} valid paths", paths.len())
+        Ok(format!("There are {} valid paths", paths))
     }
 }
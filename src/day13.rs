@@ -1,64 +1,49 @@
-use crate::Solution;
+use crate::{
+    context::Context,
+    geometry::{Aabb, Axis, Point},
+    parse,
+    svg::Svg,
+    Solution,
+};
 use itertools::Itertools;
-use std::{cmp::max, fmt, io::BufRead};
-
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    X,
-    Y,
-}
+use std::{fmt, io::BufRead};
 
 #[derive(Debug, Clone, Copy)]
 struct Fold {
-    direction: Direction,
-    index: u32,
+    axis: Axis,
+    at: i64,
 }
 
-type Point = (u32, u32);
-
 struct Origami {
-    max_x: u32,
-    max_y: u32,
     points: Vec<Point>,
     folds: Vec<Fold>,
 }
 
 impl Origami {
     fn do_fold(&mut self, fold: &Fold) {
-        match fold.direction {
-            Direction::X => {
-                let original_max_x = self.max_x;
-                self.max_x = 0;
-                for (x, _) in self.points.iter_mut() {
-                    if *x > fold.index {
-                        *x = original_max_x - *x;
-                        if *x > self.max_x {
-                            self.max_x = *x;
-                        }
-                    }
-                }
-            }
-            Direction::Y => {
-                let original_max_y = self.max_y;
-                self.max_y = 0;
-                for (_, y) in self.points.iter_mut() {
-                    if *y > fold.index {
-                        *y = original_max_y - *y;
-                        if *y > self.max_y {
-                            self.max_y = *y;
-                        }
-                    }
-                }
+        for point in self.points.iter_mut() {
+            let coordinate = match fold.axis {
+                Axis::X => point.x,
+                Axis::Y => point.y,
+            };
+            if coordinate > fold.at {
+                *point = point.reflect(fold.axis, fold.at);
             }
         }
     }
+
+    /// The bottom right corner of the paper, as far as there are dots on it
+    fn max(&self) -> Point {
+        Aabb::from_points(self.points.iter().copied()).map_or(Point::ORIGIN, |aabb| aabb.max)
+    }
 }
 
 impl fmt::Display for Origami {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in 0..=self.max_y {
-            for x in 0..=self.max_x {
-                if self.points.contains(&(x, y)) {
+        let max = self.max();
+        for y in 0..=max.y {
+            for x in 0..=max.x {
+                if self.points.contains(&Point::new(x, y)) {
                     write!(f, "█")?;
                 } else {
                     write!(f, " ")?;
@@ -73,8 +58,7 @@ impl fmt::Display for Origami {
 impl fmt::Debug for Origami {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}\n", self)?;
-        write!(f, "max_x: {}\n", self.max_x)?;
-        write!(f, "max_y: {}\n", self.max_y)?;
+        write!(f, "max: {}\n", self.max())?;
         for fold in &self.folds {
             write!(f, "{:?}\n", fold)?;
         }
@@ -83,72 +67,97 @@ impl fmt::Debug for Origami {
     }
 }
 
+fn read_fold(line: &str) -> Result<Fold, String> {
+    let invalid = || format!("expected a fold like 'fold along y=7', found '{}'", line);
+    let (direction, index) = line
+        .strip_prefix("fold along ")
+        .and_then(|fold| fold.split_once('='))
+        .ok_or_else(invalid)?;
+
+    Ok(Fold {
+        axis: match direction {
+            "x" => Axis::X,
+            "y" => Axis::Y,
+            _ => return Err(invalid()),
+        },
+        at: parse::value(index)?,
+    })
+}
+
 fn read_input(buf: &mut impl BufRead) -> Origami {
-    let mut origami = Origami {
-        max_x: 0,
-        max_y: 0,
-        points: Vec::new(),
-        folds: Vec::new(),
+    let text = parse::read_to_string(buf);
+    let sections = parse::sections(&text);
+    let [points, folds] = &sections[..] else {
+        panic!("Expected points and folds separated by a blank line");
     };
 
-    let mut lines = buf.lines().map(Result::unwrap);
-
-    for line in lines.by_ref() {
-        if let [x_str, y_str] = line.split(",").collect::<Vec<&str>>()[..] {
-            let x = x_str.parse().expect("Invalid point line");
-            let y = y_str.parse().expect("Invalid point line");
-
-            origami.max_x = max(origami.max_x, x);
-            origami.max_y = max(origami.max_y, y);
-
-            origami.points.push((x, y));
-        } else if line.is_empty() {
-            break;
-        } else {
-            panic!("Invalid point line");
-        }
+    Origami {
+        points: points
+            .parse(|text| parse::lines(text, parse::point))
+            .unwrap()
+            .into_iter()
+            .map(Point::from)
+            .collect(),
+        folds: folds.parse(|text| parse::lines(text, read_fold)).unwrap(),
     }
+}
 
-    for line in lines.by_ref() {
-        if let [beginning, num_str] = line.split("=").collect::<Vec<&str>>()[..] {
-            origami.folds.append(&mut vec![Fold {
-                direction: match beginning.chars().last() {
-                    Some('x') => Direction::X,
-                    Some('y') => Direction::Y,
-                    _ => panic!("Invalid fold line"),
-                },
-                index: num_str.parse().expect("Invalid point line"),
-            }]);
-        } else {
-            panic!("Invalid fold line");
-        }
+/// Draw the dots after all folds, which spell out the code
+pub fn render(buf: &mut impl BufRead) -> Svg {
+    const SCALE: u32 = 10;
+
+    let mut origami = read_input(buf);
+    let folds = origami.folds.clone();
+    folds.iter().for_each(|fold| {
+        origami.do_fold(fold);
+    });
+
+    let max = origami.max();
+    let mut svg = Svg::new(
+        SCALE * (max.x as u32 + 1),
+        SCALE * (max.y as u32 + 1),
+        "#000000",
+    );
+    for point in origami.points.iter().unique() {
+        svg.rect(
+            SCALE * point.x as u32,
+            SCALE * point.y as u32,
+            SCALE,
+            SCALE,
+            "#ffffff",
+        );
     }
 
-    origami
+    svg
 }
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 13;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut origami = read_input(buf);
         let fold = origami.folds[0].clone();
 
         origami.do_fold(&fold);
         let point_count = origami.points.into_iter().unique().count();
 
-        format!("There are {} points after the first iteration", point_count)
+        Ok(format!(
+            "There are {} points after the first iteration",
+            point_count
+        ))
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 13;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut origami = read_input(buf);
         let folds = origami.folds.clone();
 
@@ -156,6 +165,6 @@ impl Solution for Part2 {
             origami.do_fold(&fold);
         });
 
-        format!("\n{}", origami)
+        Ok(format!("\n{}", origami))
     }
 }
@@ -1,13 +1,14 @@
 use std::io::BufRead;
 
-use crate::Solution;
+use crate::{context::Context, Solution};
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 2;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut depth = 0;
         let mut horizontal = 0;
 
@@ -31,21 +32,22 @@ impl Solution for Part1 {
             }
         }
 
-        format!(
+        Ok(format!(
             "Horizontal={}, Depth={}, Product={}",
             horizontal,
             depth,
             horizontal * depth
-        )
+        ))
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 2;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let mut depth = 0;
         let mut horizontal = 0;
         let mut aim = 0;
@@ -71,11 +73,11 @@ impl Solution for Part2 {
             }
         }
 
-        format!(
+        Ok(format!(
             "Horizontal={}, Depth={}, Product={}",
             horizontal,
             depth,
             horizontal * depth
-        )
+        ))
     }
 }
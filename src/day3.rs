@@ -1,6 +1,6 @@
 use std::io::BufRead;
 
-use crate::Solution;
+use crate::{context::Context, Solution};
 
 fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
     assert!(!v.is_empty());
@@ -33,10 +33,11 @@ fn find_most_common(column: &Vec<char>) -> char {
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 3;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let lines: Vec<Vec<char>> = buf.lines().map(|l| l.unwrap().chars().collect()).collect();
 
         let most_common: Vec<char> = transpose(lines).iter().map(find_most_common).collect();
@@ -56,12 +57,12 @@ impl Solution for Part1 {
         let gamma = isize::from_str_radix(&gamma_str, 2).unwrap();
         let epsilon = isize::from_str_radix(&epsilon_str, 2).unwrap();
 
-        format!(
+        Ok(format!(
             "Gamma={}, Epsilon={}, Power Consumption={}",
             gamma,
             epsilon,
             gamma * epsilon
-        )
+        ))
     }
 }
 
@@ -71,10 +72,11 @@ fn vec_as_string(v: &Vec<char>) -> String {
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 3;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let lines: Vec<Vec<char>> = buf.lines().map(|l| l.unwrap().chars().collect()).collect();
 
         let mut oxygen_lines = lines.clone();
@@ -109,21 +111,21 @@ impl Solution for Part2 {
             let bitstr: String = (oxygen_lines[0].clone()).into_iter().collect();
             isize::from_str_radix(&bitstr, 2).unwrap()
         } else {
-            return "Could not determine oxygen rating".to_string();
+            return Err("Could not determine oxygen rating".to_string());
         };
 
         let co2_rating: isize = if co2_lines.len() == 1 {
             let bitstr: String = (co2_lines[0].clone()).into_iter().collect();
             isize::from_str_radix(&bitstr, 2).unwrap()
         } else {
-            return "Could not determine co2 rating".to_string();
+            return Err("Could not determine co2 rating".to_string());
         };
 
-        format!(
+        Ok(format!(
             "Oxygen rating: {} | CO2 rating: {} | Life support rating: {}",
             oxygen_rating,
             co2_rating,
             oxygen_rating * co2_rating
-        )
+        ))
     }
 }
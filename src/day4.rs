@@ -2,7 +2,13 @@ use ansi_term::{Colour, Style};
 use core::fmt;
 use std::{fmt::Display, io::BufRead};
 
-use crate::{Extra, Solution};
+use crate::{
+    context::{Context, Level},
+    params::Params,
+    parse::{self, ParseError},
+    visualisation::WindowSettings,
+    Extra, Solution,
+};
 
 #[derive(Debug)]
 enum Bingo {
@@ -41,45 +47,86 @@ fn check_bingo<const N: usize>(board: &[[(bool, i32); N]; N]) -> Option<Bingo> {
     return None;
 }
 
-fn read_input(buf: &mut impl BufRead) -> (Vec<i32>, Vec<[[(bool, i32); 5]; 5]>) {
-    let mut lines = buf.lines().map(|l| l.unwrap());
+type Board = [[(bool, i32); 5]; 5];
 
-    let first_line = lines.next().unwrap();
-    let sequence: Vec<i32> = first_line
-        .split(",")
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect();
-
-    let mut boards: Vec<[[(bool, i32); 5]; 5]> = Vec::new();
+fn read_board(text: &str) -> Result<Board, ParseError> {
+    let rows = parse::lines(text, parse::whitespace_separated::<i32>)?;
+    if rows.len() != 5 {
+        return Err(ParseError::new(
+            rows.len().min(6),
+            format!("expected a board of 5 rows, found {}", rows.len()),
+        ));
+    }
 
-    let mut board_line = 0;
-    for line in lines {
-        if line.is_empty() {
-            board_line = 0;
-            boards.push([[(false, 0); 5]; 5]);
-            continue;
+    let mut board = [[(false, 0); 5]; 5];
+    for (row_idx, row) in rows.iter().enumerate() {
+        if row.len() != 5 {
+            return Err(ParseError::new(
+                row_idx + 1,
+                format!("expected 5 numbers, found {}", row.len()),
+            ));
+        }
+        for (col_idx, num) in row.iter().enumerate() {
+            board[row_idx][col_idx] = (false, *num);
         }
+    }
+    Ok(board)
+}
 
-        for (idx, num) in line
-            .split_whitespace()
-            .map(|n| n.parse::<i32>().unwrap())
-            .enumerate()
-        {
-            boards.last_mut().unwrap()[board_line][idx] = (false, num);
+/// All boards one after the other, with the numbers marked so far highlighted and `draw` standing
+/// out
+fn format_boards(boards: &[Board], draw: i32) -> String {
+    let normal: Style = Style::new().dimmed();
+    let marked: Style = Style::new().fg(Colour::Cyan);
+    let just_marked: Style = Style::new().underline().bold().fg(Colour::Red);
+
+    let mut text = String::new();
+    for board in boards {
+        for line in board {
+            for (status, num) in line {
+                let style = if *status && *num == draw {
+                    just_marked
+                } else if *status {
+                    marked
+                } else {
+                    normal
+                };
+                text += &format!("{} ", style.paint(format!("{:>2}", num)));
+            }
+            text += "\n";
         }
-        board_line += 1;
+        text += "\n";
     }
+    text
+}
 
-    return (sequence, boards);
+fn read_input(buf: &mut impl BufRead) -> (Vec<i32>, Vec<Board>) {
+    let text = parse::read_to_string(buf);
+    let sections = parse::sections(&text);
+    let (sequence, boards) = sections.split_first().expect("The input is empty");
+
+    (
+        sequence.parse(parse::comma_separated).unwrap(),
+        boards
+            .iter()
+            .map(|board| board.parse(read_board).unwrap())
+            .collect(),
+    )
 }
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 4;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
         let (sequence, mut boards) = read_input(buf);
+        ctx.info(format_args!(
+            "Playing {} numbers on {} boards",
+            sequence.len(),
+            boards.len()
+        ));
 
         for draw in sequence {
             for (status, num) in boards.iter_mut().flatten().flatten() {
@@ -88,8 +135,8 @@ impl Solution for Part1 {
                 }
             }
 
-            // eprintln!("------------------------");
-            // print_boards(&boards);
+            ctx.debug(format_args!("Drew {}", draw));
+            ctx.dump(Level::Trace, || eprint!("{}", format_boards(&boards, draw)));
 
             for (board_num, board) in boards.iter().enumerate() {
                 if let Some(location) = check_bingo(board) {
@@ -99,29 +146,35 @@ impl Solution for Part1 {
                         .filter(|(status, _)| !*status)
                         .map(|(_, val)| val)
                         .sum();
-                    return format!(
+                    return Ok(format!(
                         "Bingo! Board {} ({}) - Score = {} * {} = {}",
                         board_num,
                         location,
                         draw,
                         score_sum,
                         draw * score_sum
-                    );
+                    ));
                 }
             }
         }
 
-        return format!("No bingo");
+        return Ok(format!("No bingo"));
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 4;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
         let (sequence, mut boards) = read_input(buf);
+        ctx.info(format_args!(
+            "Playing {} numbers on {} boards",
+            sequence.len(),
+            boards.len()
+        ));
 
         for draw in sequence {
             for (status, num) in boards.iter_mut().flatten().flatten() {
@@ -130,12 +183,13 @@ impl Solution for Part2 {
                 }
             }
 
-            // eprintln!("------------------------");
-            // print_boards(&boards);
+            ctx.debug(format_args!("Drew {}", draw));
+            ctx.dump(Level::Trace, || eprint!("{}", format_boards(&boards, draw)));
 
             if boards.len() > 1 {
                 // Drop won boards
                 boards.retain(|board| check_bingo(board).is_none());
+                ctx.debug(format_args!("{} boards left", boards.len()));
             } else if let Some(_) = check_bingo(&boards[0]) {
                 let score_sum: i32 = boards[0]
                     .iter()
@@ -143,29 +197,31 @@ impl Solution for Part2 {
                     .filter(|(status, _)| !*status)
                     .map(|(_, val)| val)
                     .sum();
-                return format!(
+                return Ok(format!(
                     "Last bingo has score = {} * {} = {}",
                     draw,
                     score_sum,
                     draw * score_sum
-                );
+                ));
             }
         }
 
-        return format!("No bingo");
+        return Ok(format!("No bingo"));
     }
 }
 
 pub struct Visualise;
 impl Extra for Visualise {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 4;
+    const NAME: &'static str = "bingo";
     const USE_SAMPLE: bool = true;
 
-    fn run(buf: &mut impl BufRead) {
-        let normal: Style = Style::new().dimmed();
-        let marked: Style = Style::new().fg(Colour::Cyan);
-        let just_marked: Style = Style::new().underline().bold().fg(Colour::Red);
-
+    fn run(
+        buf: &mut impl BufRead,
+        _window: &WindowSettings,
+        _params: &Params,
+    ) -> Result<(), String> {
         let (sequence, mut boards) = read_input(buf);
         let mut input = String::new();
 
@@ -178,24 +234,9 @@ impl Extra for Visualise {
 
             // Clear the screen
             print!("\x1B[2J\n");
-            for board in &boards {
-                for line in board {
-                    for (status, num) in line {
-                        let text = format!("{:>2}", num);
-                        let style = if *status && *num == draw {
-                            just_marked
-                        } else if *status {
-                            marked
-                        } else {
-                            normal
-                        };
-                        print!("{} ", style.paint(text));
-                    }
-                    println!();
-                }
-                println!();
-            }
+            print!("{}", format_boards(&boards, draw));
             std::io::stdin().read_line(&mut input).unwrap();
         }
+        Ok(())
     }
 }
@@ -1,13 +1,9 @@
 use std::io::BufRead;
 
-use crate::Solution;
+use crate::{context::Context, parse, Solution};
 
 fn read_input(buf: &mut impl BufRead) -> Vec<u8> {
-    let line = buf.lines().next().unwrap().unwrap();
-    line.split(",")
-        .map(str::parse::<u8>)
-        .map(Result::unwrap)
-        .collect()
+    parse::comma_separated(&parse::read_to_string(buf)).unwrap()
 }
 
 fn special_lanternfish(initial: Vec<u8>, days: u16) -> String {
@@ -50,22 +46,47 @@ fn fishbuckets(initial: Vec<u8>, days: u16) -> String {
     format!("{} lanternfish", buckets[..].iter().sum::<u64>())
 }
 
+const DAYS: &[(&str, &str)] = &[(
+    "days",
+    "How many days to simulate, 80 for part 1 and 256 for part 2 by default",
+)];
+
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 6;
+    const PART: u8 = 1;
+    const PARAMS: &'static [(&'static str, &'static str)] = DAYS;
+
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
+        let days = ctx.params().get_or("days", 80)?;
+        Ok(fishbuckets(read_input(buf), days))
+    }
+}
+
+/// Simulates every fish individually, only feasible for part 1
+pub struct Part1Naive;
+impl Solution for Part1Naive {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 6;
     const PART: u8 = 1;
+    const PARAMS: &'static [(&'static str, &'static str)] = DAYS;
 
-    fn solve(buf: &mut impl BufRead) -> String {
-        fishbuckets(read_input(buf), 80)
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
+        let days = ctx.params().get_or("days", 80)?;
+        Ok(special_lanternfish(read_input(buf), days))
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 6;
     const PART: u8 = 2;
+    const PARAMS: &'static [(&'static str, &'static str)] = DAYS;
 
-    fn solve(buf: &mut impl BufRead) -> String {
-        fishbuckets(read_input(buf), 256)
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
+        let days = ctx.params().get_or("days", 256)?;
+        Ok(fishbuckets(read_input(buf), days))
     }
 }
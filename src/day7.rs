@@ -3,19 +3,19 @@ use std::collections::HashMap;
 use std::io::BufRead;
 
 use sdl2::pixels::Color;
-use sdl2::rect::{Point, Rect};
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use sdl2::rect::Point;
+use sdl2::render::{Canvas, RenderTarget};
 
-use crate::visualisation::WindowApp;
-use crate::{Extra, Solution};
+use crate::geometry::{self, Aabb};
+use crate::visualisation::{
+    camera::Camera,
+    simulation::{Animation, Renderer, Simulation},
+    WindowApp, WindowSettings,
+};
+use crate::{context::Context, params::Params, parse, Extra, Solution};
 
 fn read_input(buf: &mut impl BufRead) -> Vec<i32> {
-    let line = buf.lines().next().unwrap().unwrap();
-    line.split(",")
-        .map(str::parse)
-        .map(Result::unwrap)
-        .collect()
+    parse::comma_separated(&parse::read_to_string(buf)).unwrap()
 }
 
 impl Part1 {
@@ -32,17 +32,18 @@ impl Part1 {
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 7;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let nums = read_input(buf);
         let align_spot = Self::align_spot(&nums);
         let fuel: i32 = nums.iter().map(|n| Self::cost(align_spot, *n)).sum();
-        format!(
+        Ok(format!(
             "The crabs will need {} fuel to align at {}",
             fuel, align_spot
-        )
+        ))
     }
 }
 
@@ -70,10 +71,11 @@ impl Part2 {
 }
 
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 7;
     const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let start_positions = read_input(buf);
 
         let align_spot = Self::align_spot(&start_positions);
@@ -82,64 +84,139 @@ impl Solution for Part2 {
             .map(|crab| Self::cost(*crab, align_spot))
             .sum::<i32>();
 
-        format!(
+        Ok(format!(
             "The crabs will need {} fuel to align at {}",
             cost, align_spot
-        )
+        ))
     }
 }
 
-//---- Extra Visualisation --------------------------------
-
-impl Extra for Visualise {
+/// Only checks the positions around the mean, which is where the optimal position is
+pub struct Part2Mean;
+impl Solution for Part2Mean {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 7;
-    const USE_SAMPLE: bool = false;
+    const PART: u8 = 2;
 
-    fn run(buf: &mut impl BufRead) {
-        Visualise::new(read_input(buf)).run_window();
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
+        let start_positions = read_input(buf);
+
+        let total_cost = |target: i32| {
+            start_positions
+                .iter()
+                .map(|crab| Part2::cost(*crab, target))
+                .sum::<i32>()
+        };
+        let mean = start_positions.iter().sum::<i32>() / start_positions.len() as i32;
+        let align_spot = ((mean - 1).max(0)..=mean + 1)
+            .min_by_key(|target| total_cost(*target))
+            .unwrap();
+
+        Ok(format!(
+            "The crabs will need {} fuel to align at {}",
+            total_cost(align_spot),
+            align_spot
+        ))
     }
 }
 
-pub struct Visualise {
-    positions: Vec<i32>,
+//---- Extra Visualisation --------------------------------
+
+/// The crabs moving towards where part 1 aligns them, `step_size` positions per step
+pub struct Crabs {
     start_positions: Vec<i32>,
     target_position: i32,
-    done: bool,
-
-    crab_height: f32,
-    crab_spacing: f32,
     step_size: i32,
 }
 
-impl Visualise {
-    fn new(positions: Vec<i32>) -> Self {
+impl Crabs {
+    pub fn new(positions: Vec<i32>) -> Self {
         Self {
             target_position: Part1::align_spot(&positions),
-            start_positions: positions.clone(),
-            positions,
-            done: false,
-            crab_height: 5.,
-            crab_spacing: 1.,
+            start_positions: positions,
             step_size: 1,
         }
     }
+
+    pub fn target_position(&self) -> i32 {
+        self.target_position
+    }
+}
+
+impl Simulation for Crabs {
+    type State = Vec<i32>;
+
+    fn initial(&self) -> Vec<i32> {
+        self.start_positions.clone()
+    }
+
+    fn step(&self, positions: &mut Vec<i32>) {
+        for crab_x in positions.iter_mut() {
+            if *crab_x < self.target_position {
+                *crab_x += min(self.step_size, self.target_position - *crab_x);
+            } else if *crab_x > self.target_position {
+                *crab_x -= min(self.step_size, *crab_x - self.target_position);
+            }
+        }
+    }
+
+    fn finished(&self, positions: &Vec<i32>) -> bool {
+        positions
+            .iter()
+            .all(|crab_x| *crab_x == self.target_position)
+    }
+}
+
+impl Extra for Visualise {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 7;
+    const NAME: &'static str = "crabs";
+    const USE_SAMPLE: bool = false;
+    const PARAMS: &'static [(&'static str, &'static str)] = &[(
+        "zoom",
+        "Pixels per position, fits the crabs to the window by default",
+    )];
+
+    fn run(buf: &mut impl BufRead, window: &WindowSettings, params: &Params) -> Result<(), String> {
+        let renderer = Self {
+            zoom: params.get("zoom")?,
+        };
+        Animation::new(Crabs::new(read_input(buf)), renderer).run_window(window);
+        Ok(())
+    }
 }
 
-impl WindowApp for Visualise {
+pub struct Visualise {
+    /// Pixels per position to start with, fitted to the window if not given
+    zoom: Option<f64>,
+}
+
+impl Renderer<Crabs> for Visualise {
     const WINDOW_NAME: &'static str = "Day 7 - Crabs";
     const WINDOW_WIDTH: u32 = 1200;
     const WINDOW_HEIGHT: u32 = 1000;
-    const WINDOW_FPS: Option<u32> = Some(165);
-
-    fn reset(&mut self) {
-        self.positions = self.start_positions.clone();
+    const STEPS_PER_SECOND: f64 = 165.;
+
+    /// The crabs as far right as they start, one row each
+    fn content(&self, crabs: &Crabs) -> Option<Aabb> {
+        let right = crabs.start_positions.iter().max().copied().unwrap_or(0) + 8;
+        Some(Aabb {
+            min: geometry::Point::ORIGIN,
+            max: geometry::Point::new(right as i64, crabs.start_positions.len() as i64),
+        })
     }
 
-    fn draw_frame(&mut self, canvas: &mut Canvas<Window>) -> Result<bool, String> {
-        if self.done {
-            return Ok(true);
-        }
+    fn zoom(&self) -> Option<f64> {
+        self.zoom
+    }
 
+    fn draw<T: RenderTarget>(
+        &mut self,
+        crabs: &Crabs,
+        positions: &Vec<i32>,
+        canvas: &mut Canvas<T>,
+        camera: &Camera,
+    ) -> Result<(), String> {
         canvas.set_draw_color(Color::RGB(0, 0, 0));
         canvas.clear();
 
@@ -147,34 +224,30 @@ impl WindowApp for Visualise {
         let mut y = 0;
 
         let mut counter: HashMap<i32, i32> = HashMap::new();
-        for crab_x in &self.positions {
+        for crab_x in positions {
             *counter.entry(*crab_x).or_insert(0) += 1;
             y += 1;
             canvas.set_draw_color(Color::RED);
-            canvas.fill_rect(Rect::new(*crab_x, y, 8, 1))?
+            canvas.fill_rect(camera.rect(*crab_x as f64, y as f64, 8., 1.))?
         }
 
+        // The number of crabs at each position, standing on the bottom of the window
         canvas.set_draw_color(Color::WHITE);
-        let points = (0..Self::WINDOW_WIDTH)
+        let (_, screen_height) = camera.screen_size();
+        let (_, bottom) = camera.to_world(0, screen_height as i32);
+        let right = self
+            .content(crabs)
+            .map_or(0, |content| content.max.x as i32);
+        let points = (0..=right)
             .map(|x| {
-                Point::new(
-                    x as i32,
-                    Self::WINDOW_HEIGHT as i32 - 20 * *counter.get(&(x as i32)).unwrap_or(&0),
+                camera.point(
+                    x as f64,
+                    bottom - 20. * *counter.get(&x).unwrap_or(&0) as f64,
                 )
             })
             .collect::<Vec<Point>>();
         canvas.draw_lines(&points[..])?;
 
-        for crab_x in self.positions.iter_mut() {
-            if *crab_x != self.target_position {
-                if *crab_x < self.target_position {
-                    *crab_x += min(self.step_size, self.target_position - *crab_x);
-                } else if *crab_x > self.target_position {
-                    *crab_x -= min(self.step_size, *crab_x - self.target_position);
-                }
-            }
-        }
-
-        Ok(true)
+        Ok(())
     }
 }
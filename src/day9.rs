@@ -1,74 +1,85 @@
-use std::io::BufRead;
+use std::{
+    collections::{HashSet, VecDeque},
+    io::BufRead,
+};
 
 use ansi_term::{Colour, Style};
-use sdl2::{pixels::Color, rect::Rect, render::Canvas, video::Window};
-
-use crate::{util::neighbours, visualisation::WindowApp, Extra, Solution};
-
-type HeightMap = Vec<Vec<u8>>;
-type Basin = Vec<(usize, usize)>;
+use sdl2::{
+    pixels::Color,
+    rect::Rect,
+    render::{Canvas, RenderTarget},
+};
+
+use crate::{
+    context::{Context, Level},
+    geometry::Aabb,
+    params::Params,
+    parse,
+    svg::Svg,
+    util::{Grid, Position},
+    visualisation::{
+        camera::Camera,
+        simulation::{Animation, Renderer, Simulation},
+        WindowApp, WindowSettings,
+    },
+    Extra, Solution,
+};
+
+type HeightMap = Grid<u8>;
+type Basin = Vec<Position>;
 
 fn read_input(buf: &mut impl BufRead) -> HeightMap {
-    buf.lines()
-        .map(Result::unwrap)
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect()
-        })
-        .collect()
+    Grid::from_digits(&parse::read_to_string(buf)).unwrap()
 }
 
-fn has_basin_at(map: &HeightMap, row: usize, col: usize) -> bool {
-    neighbours(map, row, col)
-        // Check that they are all heigher than [row][col]
-        .all(|((_nrow, _ncol), neighbour)| map[row][col] < *neighbour)
+fn has_basin_at(map: &HeightMap, position: Position) -> bool {
+    map.neighbours(position)
+        // Check that they are all heigher than the location itself
+        .all(|(_, neighbour)| map[position] < *neighbour)
 }
 
 pub struct Part1;
 impl Solution for Part1 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 9;
     const PART: u8 = 1;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
         let grid = read_input(buf);
         let mut risk_level: u32 = 0;
 
-        for (row_idx, row) in grid.iter().enumerate() {
-            for (col_idx, height) in row.iter().enumerate() {
-                if has_basin_at(&grid, row_idx, col_idx) {
-                    risk_level += *height as u32 + 1;
-                }
+        for (position, height) in grid.iter() {
+            if has_basin_at(&grid, position) {
+                risk_level += *height as u32 + 1;
             }
         }
 
-        format!("The risk level is: {}", risk_level)
+        Ok(format!("The risk level is: {}", risk_level))
     }
 }
 
 fn map_basin(
     map: &HeightMap,
-    row: usize,
-    col: usize,
-    basin_locations: &mut Vec<(usize, usize)>,
+    position: Position,
+    basin_locations: &mut Vec<Position>,
     print_cb: &mut impl FnMut(&Basin),
 ) {
-    if map[row][col] >= 9 {
+    if map[position] >= 9 {
         return;
     }
 
-    basin_locations.push((row, col));
+    basin_locations.push(position);
 
     print_cb(basin_locations);
 
-    for ((nrow, ncol), neighbour) in neighbours(map, row, col) {
-        if basin_locations.contains(&(nrow, ncol)) {
+    for (neighbour_position, neighbour) in map.neighbours(position) {
+        if basin_locations.contains(&neighbour_position) {
             // Skip locations we've already mapped
             continue;
         }
 
         if *neighbour < 9 {
-            map_basin(map, nrow, ncol, basin_locations, print_cb)
+            map_basin(map, neighbour_position, basin_locations, print_cb)
         }
     }
 }
@@ -78,134 +89,334 @@ fn print_basin(map: &HeightMap, basin: &Basin) {
     let bottom: Style = Style::new().bold().fg(Colour::Red);
     let in_basin: Style = Style::new().bold().fg(Colour::Cyan);
 
-    for (row_idx, row) in map.iter().enumerate() {
-        for (col_idx, height) in row.iter().enumerate() {
-            let style = if basin[0] == (row_idx, col_idx) {
-                bottom
-            } else if basin.contains(&(row_idx, col_idx)) {
-                in_basin
-            } else {
-                normal
-            };
-            eprint!("{}", style.paint(format!("{}", height)))
+    for ((row, col), height) in map.iter() {
+        let style = if basin[0] == (row, col) {
+            bottom
+        } else if basin.contains(&(row, col)) {
+            in_basin
+        } else {
+            normal
+        };
+        eprint!("{}", style.paint(format!("{}", height)));
+        if col == map.width() - 1 {
+            eprintln!();
         }
-        eprintln!("");
     }
 }
 
 pub struct Part2;
 impl Solution for Part2 {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 9;
-    const PART: u8 = 1;
+    const PART: u8 = 2;
 
-    fn solve(buf: &mut impl BufRead) -> String {
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String> {
         let map = read_input(buf);
         let mut basins: Vec<Basin> = vec![];
 
-        for (row_idx, row) in map.iter().enumerate() {
-            for (col_idx, _height) in row.iter().enumerate() {
-                if has_basin_at(&map, row_idx, col_idx) {
-                    let mut basin = vec![];
-                    map_basin(&map, row_idx, col_idx, &mut basin, &mut |_| {});
-                    basins.push(basin);
-                }
+        for (position, _height) in map.iter() {
+            if has_basin_at(&map, position) {
+                let mut basin = vec![];
+                map_basin(&map, position, &mut basin, &mut |_| {});
+                ctx.debug(format_args!(
+                    "The basin at {:?} has size {}",
+                    position,
+                    basin.len()
+                ));
+                ctx.dump(Level::Trace, || print_basin(&map, &basin));
+                basins.push(basin);
             }
         }
 
         basins.sort_by_key(Vec::len);
-        format!(
+        Ok(format!(
             "The top 3 basins' sizes multiplied together give: {}",
             basins.iter().rev().take(3).map(Vec::len).product::<usize>()
-        )
+        ))
     }
 }
 
-pub struct Progression {
+/// All locations in the basin with its low point at `low_point`, found breadth-first
+fn flood_basin(map: &HeightMap, low_point: Position) -> HashSet<Position> {
+    let mut visited = HashSet::from([low_point]);
+    let mut frontier = VecDeque::from([low_point]);
+    while let Some(position) = frontier.pop_front() {
+        for (location, neighbour) in map.neighbours(position) {
+            if *neighbour < 9 && visited.insert(location) {
+                frontier.push_back(location);
+            }
+        }
+    }
+    visited
+}
+
+/// Flood fills each basin breadth-first, keeping track of visited locations in a set
+pub struct Part2Flood;
+impl Solution for Part2Flood {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 9;
+    const PART: u8 = 2;
+
+    fn solve(buf: &mut impl BufRead, _ctx: &Context) -> Result<String, String> {
+        let map = read_input(buf);
+        let mut basin_sizes: Vec<usize> = vec![];
+
+        for (position, _height) in map.iter() {
+            if has_basin_at(&map, position) {
+                basin_sizes.push(flood_basin(&map, position).len());
+            }
+        }
+
+        basin_sizes.sort_unstable();
+        Ok(format!(
+            "The top 3 basins' sizes multiplied together give: {}",
+            basin_sizes.iter().rev().take(3).product::<usize>()
+        ))
+    }
+}
+
+/// Draw the height map with each basin in its own colour
+pub fn render(buf: &mut impl BufRead) -> Svg {
+    const SCALE: u32 = 5;
+
+    let map = read_input(buf);
+    let mut svg = Svg::new(
+        SCALE * map.width() as u32,
+        SCALE * map.height() as u32,
+        "#111111",
+    );
+
+    let mut basin_count = 0;
+    for ((row_idx, col_idx), height) in map.iter() {
+        if *height >= 9 {
+            let (x, y) = (SCALE * col_idx as u32, SCALE * row_idx as u32);
+            svg.rect(x, y, SCALE, SCALE, "#888888");
+        } else if has_basin_at(&map, (row_idx, col_idx)) {
+            // Spread the hues out using the golden angle
+            let colour = format!("hsl({:.0}, 70%, 50%)", basin_count as f64 * 137.5 % 360.);
+            basin_count += 1;
+            for (row, col) in flood_basin(&map, (row_idx, col_idx)) {
+                let (x, y) = (SCALE * col as u32, SCALE * row as u32);
+                svg.rect(x, y, SCALE, SCALE, &colour);
+            }
+        }
+    }
+
+    svg
+}
+
+/// Mapping the basins one location at a time, from each low point outwards
+pub struct Basins {
     map: HeightMap,
-    basin_views: Vec<Basin>,
-    next_basin_view: usize,
+    low_points: Vec<Position>,
+}
 
-    background_drawn: bool,
+/// How far mapping the basins has got
+#[derive(Debug, Clone)]
+pub struct Mapping {
+    /// The locations mapped as part of a basin
+    pub mapped: Grid<bool>,
+    /// Locations of the basin being mapped that are still to be looked at, the next at the back
+    to_visit: Vec<Position>,
+    /// Where in `low_points` the next basin starts
+    next_low_point: usize,
+}
+
+impl Basins {
+    pub fn new(map: HeightMap) -> Self {
+        let low_points = map
+            .iter()
+            .map(|(position, _)| position)
+            .filter(|position| has_basin_at(&map, *position))
+            .collect();
+        Self { map, low_points }
+    }
+}
+
+impl Simulation for Basins {
+    type State = Mapping;
+
+    fn initial(&self) -> Mapping {
+        Mapping {
+            mapped: self.map.map(|_| false),
+            to_visit: Vec::new(),
+            next_low_point: 0,
+        }
+    }
+
+    /// Map one more location, starting on the next basin when this one is done
+    fn step(&self, mapping: &mut Mapping) {
+        loop {
+            let position = match mapping.to_visit.pop() {
+                Some(position) => position,
+                None => match self.low_points.get(mapping.next_low_point) {
+                    Some(low_point) => {
+                        mapping.next_low_point += 1;
+                        *low_point
+                    }
+                    None => return,
+                },
+            };
+            if mapping.mapped[position] || self.map[position] >= 9 {
+                continue;
+            }
+
+            mapping.mapped[position] = true;
+            // Reversed, so that neighbours are visited in the order `map_basin` visits them
+            let mut neighbours: Vec<Position> = self
+                .map
+                .neighbours(position)
+                .filter(|(neighbour, height)| **height < 9 && !mapping.mapped[*neighbour])
+                .map(|(neighbour, _)| neighbour)
+                .collect();
+            neighbours.reverse();
+            mapping.to_visit.extend(neighbours);
+            return;
+        }
+    }
+
+    fn finished(&self, mapping: &Mapping) -> bool {
+        mapping.to_visit.is_empty() && mapping.next_low_point == self.low_points.len()
+    }
+}
+
+pub struct Progression {
+    /// Pixels per location to start with, fitted to the window if not given
+    cell_size: Option<u32>,
 }
 
 impl Extra for Progression {
+    const YEAR: u16 = 2021;
     const DAY: u8 = 9;
+    const NAME: &'static str = "progression";
     const USE_SAMPLE: bool = false;
-
-    fn run(buf: &mut impl BufRead) {
-        Self::new(read_input(buf)).run_window();
+    const PARAMS: &'static [(&'static str, &'static str)] = &[(
+        "cell_size",
+        "Size of a location in pixels, fits the map to the window by default",
+    )];
+
+    fn run(buf: &mut impl BufRead, window: &WindowSettings, params: &Params) -> Result<(), String> {
+        let renderer = Self {
+            cell_size: params.get("cell_size")?,
+        };
+        Animation::new(Basins::new(read_input(buf)), renderer).run_window(window);
+        Ok(())
     }
 }
 
-impl Progression {
-    fn new(map: HeightMap) -> Self {
-        let mut basin_views: Vec<Basin> = Vec::new();
+impl Renderer<Basins> for Progression {
+    const WINDOW_NAME: &'static str = "Day 9 - Basins";
+    const WINDOW_WIDTH: u32 = 1000;
+    const WINDOW_HEIGHT: u32 = 1000;
+    const STEPS_PER_SECOND: f64 = 60.;
+
+    fn content(&self, basins: &Basins) -> Option<Aabb> {
+        Some(basins.map.bounds())
+    }
+
+    fn zoom(&self) -> Option<f64> {
+        self.cell_size.map(f64::from)
+    }
 
-        for (row_idx, row) in map.iter().enumerate() {
-            for (col_idx, _height) in row.iter().enumerate() {
-                if has_basin_at(&map, row_idx, col_idx) {
-                    let mut basin = vec![];
-                    map_basin(&map, row_idx, col_idx, &mut basin, &mut |basin_view| {
-                        basin_views.push(basin_view.clone())
-                    });
-                }
+    fn draw<T: RenderTarget>(
+        &mut self,
+        basins: &Basins,
+        mapping: &Mapping,
+        canvas: &mut Canvas<T>,
+        camera: &Camera,
+    ) -> Result<(), String> {
+        canvas.set_draw_color(Color::RGB(0x11, 0x11, 0x11));
+        canvas.clear();
+
+        canvas.set_draw_color(Color::RGB(0x88, 0x88, 0x88));
+        for (position, height) in basins.map.iter() {
+            if *height >= 9 {
+                canvas.fill_rect(camera.cell(position))?;
             }
         }
 
+        canvas.set_draw_color(Color::BLUE);
+        canvas.fill_rects(
+            &mapping
+                .mapped
+                .iter()
+                .filter(|(_, mapped)| **mapped)
+                .map(|(position, _)| camera.cell(position))
+                .collect::<Vec<Rect>>(),
+        )
+    }
+}
+
+/// The height map in shades of grey, with the low points in red
+pub struct Heightmap {
+    map: HeightMap,
+    /// Pixels per location to start with, fitted to the window if not given
+    cell_size: Option<u32>,
+    /// The camera the map was last drawn with
+    drawn_with: Option<Camera>,
+}
+
+impl Extra for Heightmap {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 9;
+    const NAME: &'static str = "heightmap";
+    const USE_SAMPLE: bool = false;
+    const PARAMS: &'static [(&'static str, &'static str)] = &[(
+        "cell_size",
+        "Size of a location in pixels, fits the map to the window by default",
+    )];
+
+    fn run(buf: &mut impl BufRead, window: &WindowSettings, params: &Params) -> Result<(), String> {
         Self {
-            map,
-            basin_views,
-            next_basin_view: 0,
-            background_drawn: false,
+            map: read_input(buf),
+            cell_size: params.get("cell_size")?,
+            drawn_with: None,
         }
+        .run_window(window);
+        Ok(())
     }
 }
 
-impl WindowApp for Progression {
-    const WINDOW_NAME: &'static str = "Day 9 - Basins";
+impl WindowApp for Heightmap {
+    const WINDOW_NAME: &'static str = "Day 9 - Height map";
     const WINDOW_WIDTH: u32 = 1000;
     const WINDOW_HEIGHT: u32 = 1000;
-    const WINDOW_FPS: Option<u32> = Some(60);
+    const WINDOW_FPS: Option<u32> = Some(30);
 
     fn reset(&mut self) {
-        self.background_drawn = false;
-        self.next_basin_view = 0;
-    }
-
-    fn draw_frame(&mut self, canvas: &mut Canvas<Window>) -> Result<bool, String> {
-        if !self.background_drawn {
-            canvas.set_draw_color(Color::RGB(0x11, 0x11, 0x11));
-            canvas.clear();
-
-            canvas.set_draw_color(Color::RGB(0x88, 0x88, 0x88));
-            for (row_idx, row) in self.map.iter().enumerate() {
-                for (col_idx, height) in row.iter().enumerate() {
-                    if *height >= 9 {
-                        canvas.fill_rect(Rect::new(
-                            10 * col_idx as i32,
-                            10 * row_idx as i32,
-                            10,
-                            10,
-                        ))?;
-                    }
-                }
-            }
+        self.drawn_with = None;
+    }
+
+    fn content(&self) -> Option<Aabb> {
+        Some(self.map.bounds())
+    }
+
+    fn zoom(&self) -> Option<f64> {
+        self.cell_size.map(f64::from)
+    }
 
-            self.background_drawn = true;
+    fn draw_frame<T: RenderTarget>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        camera: &Camera,
+    ) -> Result<bool, String> {
+        if self.drawn_with == Some(*camera) {
+            return Ok(false);
         }
 
-        if let Some(basin) = self.basin_views.get(self.next_basin_view) {
-            self.next_basin_view += 1;
-            canvas.set_draw_color(Color::BLUE);
-            canvas.fill_rects(
-                &basin
-                    .iter()
-                    .map(|(row, col)| Rect::new(10 * *col as i32, 10 * *row as i32, 10, 10))
-                    .collect::<Vec<Rect>>()[..],
-            )?;
-            Ok(true)
-        } else {
-            Ok(false)
+        canvas.set_draw_color(Color::RGB(0x11, 0x11, 0x11));
+        canvas.clear();
+        for (position, height) in self.map.iter() {
+            if has_basin_at(&self.map, position) {
+                canvas.set_draw_color(Color::RED);
+            } else {
+                let shade = 0x11 + 0x18 * *height;
+                canvas.set_draw_color(Color::RGB(shade, shade, shade));
+            }
+            canvas.fill_rect(camera.cell(position))?;
         }
+
+        self.drawn_with = Some(*camera);
+        Ok(true)
     }
 }
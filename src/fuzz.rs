@@ -0,0 +1,189 @@
+use std::{fs, panic};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    config::Config,
+    context::Context,
+    generate::generate,
+    registry::{Day, Implementation},
+};
+
+/// What running an implementation on an input gave. `None` means it panicked.
+type Outcome = Option<Result<String, String>>;
+
+fn run(implementation: &Implementation, input: &str) -> Outcome {
+    panic::catch_unwind(|| (implementation.solve)(input, &Context::default())).ok()
+}
+
+fn outcomes(implementations: &[&Implementation], input: &str) -> Vec<Outcome> {
+    implementations
+        .iter()
+        .map(|implementation| run(implementation, input))
+        .collect()
+}
+
+fn disagree(outcomes: &[Outcome]) -> bool {
+    outcomes.windows(2).any(|pair| pair[0] != pair[1])
+}
+
+/// Make `input` as small as possible while the implementations still disagree on it. First whole
+/// lines are removed, then single items of comma-separated lines. If none of the implementations
+/// panicked on the original input, inputs that make any of them panic are not accepted, since
+/// those are usually just malformed.
+fn shrink(implementations: &[&Implementation], input: &str) -> String {
+    let allow_panics = outcomes(implementations, input).iter().any(Option::is_none);
+    let interesting = |candidate: &str| {
+        let outcomes = outcomes(implementations, candidate);
+        disagree(&outcomes) && (allow_panics || outcomes.iter().all(Option::is_some))
+    };
+
+    let mut lines: Vec<String> = input.lines().map(str::to_string).collect();
+    let join = |lines: &[String]| lines.iter().map(|l| format!("{}\n", l)).collect::<String>();
+
+    // Remove chunks of lines, halving the chunk size whenever nothing can be removed
+    let mut chunk = lines.len() / 2;
+    while chunk > 0 {
+        let mut start = 0;
+        let mut removed_any = false;
+        while start < lines.len() {
+            let mut candidate = lines.clone();
+            candidate.drain(start..(start + chunk).min(lines.len()));
+            if !candidate.is_empty() && interesting(&join(&candidate)) {
+                lines = candidate;
+                removed_any = true;
+            } else {
+                start += chunk;
+            }
+        }
+        if !removed_any {
+            chunk /= 2;
+        }
+    }
+
+    // Remove items from comma-separated lines, one at a time
+    for line_idx in 0..lines.len() {
+        let mut item_idx = 0;
+        loop {
+            let items: Vec<&str> = lines[line_idx].split(',').collect();
+            if items.len() <= 1 || item_idx >= items.len() {
+                break;
+            }
+
+            let mut candidate = lines.clone();
+            candidate[line_idx] = items
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| *idx != item_idx)
+                .map(|(_, item)| *item)
+                .collect::<Vec<_>>()
+                .join(",");
+            if interesting(&join(&candidate)) {
+                lines = candidate;
+            } else {
+                item_idx += 1;
+            }
+        }
+    }
+
+    join(&lines)
+}
+
+/// Write a counterexample to the first free `{day}_fuzz_N` in the year's input directory
+fn save(config: &Config, day: &Day, input: &str) -> String {
+    let dir = config.year_dir(day.year);
+    fs::create_dir_all(&dir).expect("Could not create the input directory");
+    let path = (1..)
+        .map(|n| dir.join(format!("{}_fuzz_{}", day.day, n)))
+        .find(|path| !path.exists())
+        .unwrap();
+    fs::write(&path, input).expect("Could not write counterexample");
+    path.display().to_string()
+}
+
+/// Run every implementation of each part of `day` on `iterations` random inputs of up to
+/// `max_size`. The first disagreement found for each part is shrunk and saved as an input file.
+pub fn fuzz(config: &Config, day: &Day, iterations: usize, max_size: usize, seed: u64) {
+    let parts: Vec<Vec<&Implementation>> = [1, 2]
+        .iter()
+        .map(|part| {
+            day.implementations
+                .iter()
+                .filter(|i| i.part == *part)
+                .collect()
+        })
+        .filter(|imps: &Vec<&Implementation>| imps.len() > 1)
+        .collect();
+
+    if parts.is_empty() {
+        println!(
+            "Day {} has no part with more than one implementation",
+            day.day
+        );
+        return;
+    }
+    if generate(day.year, day.day, 1, seed).is_none() {
+        println!("I have no input generator for day {}", day.day);
+        return;
+    }
+
+    for imps in &parts {
+        println!(
+            "[Day {:>2}][Part {}]: Comparing {}",
+            day.day,
+            imps[0].part,
+            imps.iter().map(|i| i.name).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    // Panics are expected here, don't let them spam the output
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pending = parts;
+    for iteration in 0..iterations {
+        if pending.is_empty() {
+            break;
+        }
+
+        let size = rng.gen_range(1..=max_size.max(1));
+        let input_seed = rng.gen();
+        let input = generate(day.year, day.day, size, input_seed).unwrap();
+
+        pending.retain(|imps| {
+            let results = outcomes(imps, &input);
+            if !disagree(&results) {
+                return true;
+            }
+
+            let shrunk = shrink(imps, &input);
+            let path = save(config, day, &shrunk);
+            println!(
+                "[Day {:>2}][Part {}]: Disagreement on iteration {} (size {}, seed {}), saved to {}",
+                day.day, imps[0].part, iteration, size, input_seed, path
+            );
+            for (imp, outcome) in imps.iter().zip(outcomes(imps, &shrunk)) {
+                println!(
+                    "    {}: {}",
+                    imp.name,
+                    match outcome {
+                        Some(Ok(answer)) => answer,
+                        Some(Err(e)) => format!("failed: {}", e),
+                        None => "panicked".to_string(),
+                    }
+                );
+            }
+            false
+        });
+    }
+
+    panic::set_hook(default_hook);
+
+    for imps in pending {
+        println!(
+            "[Day {:>2}][Part {}]: No disagreements after {} inputs",
+            day.day, imps[0].part, iterations
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use std::{collections::VecDeque, fmt::Write};
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::day11;
+
+/// What the size of a generated input means for each day, for `--help`
+pub const SIZE_HELP: &str = "What the size of an input means depends on the day:
+  1: depth readings        2: commands              3: binary numbers
+  4: bingo boards          5: vent lines            6: lanternfish
+  7: crabs                 9: width and height      10: brackets per line
+  11: width and height     12: caves                13: dots";
+
+/// Generate a random, valid input for `day` of `year`. The same `size` and `seed` always produce
+/// the same input. What `size` means depends on the day, see the individual generators.
+pub fn generate(year: u16, day: u8, size: usize, seed: u64) -> Option<String> {
+    if year != 2021 {
+        return None;
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let size = size.max(1);
+
+    let input = match day {
+        1 => day1(&mut rng, size),
+        2 => day2(&mut rng, size),
+        3 => day3(&mut rng, size),
+        4 => day4(&mut rng, size),
+        5 => day5(&mut rng, size),
+        6 => day6(&mut rng, size),
+        7 => day7(&mut rng, size),
+        9 => day9(&mut rng, size),
+        10 => day10(&mut rng, size),
+        11 => day11(&mut rng, size),
+        12 => day12(&mut rng, size),
+        13 => day13(&mut rng, size),
+        _ => return None,
+    };
+
+    Some(input)
+}
+
+/// `size` depth readings
+fn day1(rng: &mut StdRng, size: usize) -> String {
+    let mut depth: i32 = rng.gen_range(100..200);
+    let mut out = String::new();
+    for _ in 0..size {
+        depth = (depth + rng.gen_range(-20..=40)).max(0);
+        writeln!(out, "{}", depth).unwrap();
+    }
+    out
+}
+
+/// `size` submarine commands
+fn day2(rng: &mut StdRng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        let command = ["forward", "down", "up"].choose(rng).unwrap();
+        writeln!(out, "{} {}", command, rng.gen_range(1..10)).unwrap();
+    }
+    out
+}
+
+/// `size` distinct binary numbers. They are made wider than 12 bits when needed so that they can
+/// all be distinct, which is what makes the life support rating well defined.
+fn day3(rng: &mut StdRng, size: usize) -> String {
+    let mut bits = 12;
+    while (1usize << bits) < 2 * size {
+        bits += 1;
+    }
+
+    let mut numbers = (0..(1usize << bits)).collect::<Vec<_>>();
+    numbers.shuffle(rng);
+
+    let mut out = String::new();
+    for n in numbers.into_iter().take(size) {
+        writeln!(out, "{:0width$b}", n, width = bits).unwrap();
+    }
+    out
+}
+
+/// `size` bingo boards. Every number gets drawn, so every board wins eventually.
+fn day4(rng: &mut StdRng, size: usize) -> String {
+    let pool_size = (size * 25 / 2).max(100);
+    let mut draws = (0..pool_size).collect::<Vec<_>>();
+    draws.shuffle(rng);
+
+    let mut out = draws.iter().join(",");
+    out.push('\n');
+
+    for _ in 0..size {
+        out.push('\n');
+        let board = rand::seq::index::sample(rng, pool_size, 25).into_vec();
+        for row in board.chunks(5) {
+            writeln!(out, "{}", row.iter().map(|n| format!("{:>2}", n)).join(" ")).unwrap();
+        }
+    }
+    out
+}
+
+/// `size` horizontal, vertical or diagonal vent lines, all within 0..1000
+fn day5(rng: &mut StdRng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        let x1: i32 = rng.gen_range(0..1000);
+        let y1: i32 = rng.gen_range(0..1000);
+        let length: i32 = rng.gen_range(1..300);
+        let (dx, dy) = *[
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ]
+        .choose(rng)
+        .unwrap();
+
+        // Shorten the line so that it stays on the board
+        let fits =
+            |l: i32| (0..1000).contains(&(x1 + dx * l)) && (0..1000).contains(&(y1 + dy * l));
+        let length = (0..=length).rev().find(|l| fits(*l)).unwrap();
+
+        writeln!(
+            out,
+            "{},{} -> {},{}",
+            x1,
+            y1,
+            x1 + dx * length,
+            y1 + dy * length
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// `size` lanternfish timers
+fn day6(rng: &mut StdRng, size: usize) -> String {
+    let timers = (0..size).map(|_| rng.gen_range(1..=5)).join(",");
+    format!("{}\n", timers)
+}
+
+/// `size` crab positions, spread over twice as many positions as there are crabs
+fn day7(rng: &mut StdRng, size: usize) -> String {
+    let spread = (2 * size).max(16);
+    let positions = (0..size).map(|_| rng.gen_range(0..spread)).join(",");
+    format!("{}\n", positions)
+}
+
+/// A `size`x`size` height map. The map is split into regions by walls of 9s, and the height in
+/// each region rises away from a single low point, so that every basin has exactly one low point.
+fn day9(rng: &mut StdRng, size: usize) -> String {
+    let seeds: Vec<(usize, usize)> = (0..(size * size / 40).max(1))
+        .map(|_| (rng.gen_range(0..size), rng.gen_range(0..size)))
+        .collect();
+    let nearest_seed = |row: usize, col: usize| {
+        (0..seeds.len())
+            .min_by_key(|idx| {
+                let (seed_row, seed_col) = seeds[*idx];
+                seed_row.abs_diff(row) + seed_col.abs_diff(col)
+            })
+            .unwrap()
+    };
+
+    // A cell is a wall if the cell below or to the right belongs to a different region, which
+    // means no two neighbouring non-wall cells can be in different regions
+    let regions: Vec<Vec<usize>> = (0..size)
+        .map(|row| (0..size).map(|col| nearest_seed(row, col)).collect())
+        .collect();
+    let mut heights: Vec<Vec<Option<u8>>> = (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|col| {
+                    let region = regions[row][col];
+                    let wall = (row + 1 < size && regions[row + 1][col] != region)
+                        || (col + 1 < size && regions[row][col + 1] != region);
+                    if wall {
+                        Some(9)
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    // Flood fill every basin outwards from its low point
+    for row in 0..size {
+        for col in 0..size {
+            if heights[row][col].is_some() {
+                continue;
+            }
+
+            let mut frontier = VecDeque::from([((row, col), 0)]);
+            heights[row][col] = Some(0);
+            while let Some(((row, col), height)) = frontier.pop_front() {
+                let neighbours = [
+                    (row.wrapping_sub(1), col),
+                    (row + 1, col),
+                    (row, col.wrapping_sub(1)),
+                    (row, col + 1),
+                ];
+                for (nrow, ncol) in neighbours {
+                    if nrow < size && ncol < size && heights[nrow][ncol].is_none() {
+                        let nheight = (height + rng.gen_range(0..=1)).clamp(1, 8);
+                        heights[nrow][ncol] = Some(nheight);
+                        frontier.push_back(((nrow, ncol), nheight));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for row in heights {
+        for height in row {
+            write!(out, "{}", height.unwrap()).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// 100 lines of `size` brackets each. Every line is either incomplete or corrupted by exactly one
+/// bracket, and there is an odd number of incomplete lines so that there is a middle score.
+fn day10(rng: &mut StdRng, size: usize) -> String {
+    const LINES: usize = 100;
+    const OPEN: [char; 4] = ['(', '[', '{', '<'];
+    const CLOSE: [char; 4] = [')', ']', '}', '>'];
+
+    let mut incomplete = (0..LINES).map(|_| rng.gen_bool(0.5)).collect::<Vec<_>>();
+    if incomplete.iter().filter(|i| **i).count() % 2 == 0 {
+        incomplete[0] = !incomplete[0];
+    }
+
+    let mut out = String::new();
+    for is_incomplete in incomplete {
+        let corrupt_at = if is_incomplete {
+            None
+        } else {
+            Some(rng.gen_range(0..size))
+        };
+        let mut corrupted = false;
+        let mut stack: Vec<usize> = Vec::new();
+
+        for idx in 0..size {
+            if !corrupted && corrupt_at.is_some_and(|at| idx >= at) && !stack.is_empty() {
+                let expected = stack.pop().unwrap();
+                out.push(CLOSE[(expected + rng.gen_range(1..4)) % 4]);
+                corrupted = true;
+            } else if stack.is_empty() || rng.gen_bool(0.55) {
+                let bracket = rng.gen_range(0..4);
+                stack.push(bracket);
+                out.push(OPEN[bracket]);
+            } else {
+                out.push(CLOSE[stack.pop().unwrap()]);
+            }
+        }
+
+        // Short lines might not have had a chance to be corrupted or left incomplete
+        if stack.is_empty() {
+            stack.push(0);
+            out.push(OPEN[0]);
+        }
+        if corrupt_at.is_some() && !corrupted {
+            out.push(CLOSE[(stack.last().unwrap() + 1) % 4]);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A `size`x`size` grid of octopus energy levels that synchronises within 1000 steps. Most random
+/// grids never synchronise, so the spread of starting energies is narrowed until one does.
+fn day11(rng: &mut StdRng, size: usize) -> String {
+    for spread in (1..=10).rev() {
+        let base = rng.gen_range(0..=10 - spread);
+        let grid = day11::Grid::from_rows(
+            (0..size)
+                .map(|_| (0..size).map(|_| base + rng.gen_range(0..spread)).collect())
+                .collect(),
+        );
+
+        let mut simulated = grid.clone();
+        if (0..1000).any(|_| day11::step_grid(&mut simulated) as usize == size * size) {
+            return grid.to_string();
+        }
+    }
+
+    unreachable!("A grid where all octopuses have the same energy always synchronises")
+}
+
+/// How many caves a room of the day 12 cave system has at most
+const DAY12_ROOM_SIZE: usize = 4;
+
+/// A cave system with `size` caves besides `start` and `end`. The caves are grouped into rooms
+/// of a few caves, which are only connected to each other through `start` and `end`. A path
+/// stays in one room, so the number of paths grows with the number of rooms rather than
+/// exploding with `size`. Big caves are never connected to each other, since that would allow
+/// infinitely many paths.
+fn day12(rng: &mut StdRng, size: usize) -> String {
+    let mut edges: Vec<(String, String)> = Vec::new();
+    let mut names = (0..).map(cave_name).filter(|name| name != "end");
+
+    let mut caves_left = size;
+    while caves_left > 0 {
+        let room_size = rng.gen_range(1..=DAY12_ROOM_SIZE.min(caves_left));
+        caves_left -= room_size;
+
+        // At most one big cave per room, so big caves are never next to each other
+        let big = rng.gen_bool(0.5).then(|| rng.gen_range(0..room_size));
+        let room: Vec<String> = (0..room_size)
+            .map(|idx| {
+                let name = names.next().unwrap();
+                if big == Some(idx) {
+                    name.to_uppercase()
+                } else {
+                    name
+                }
+            })
+            .collect();
+
+        // Connect every cave to one before it, and sometimes add a shortcut
+        for idx in 1..room_size {
+            let to = &room[rng.gen_range(0..idx)];
+            edges.push((room[idx].clone(), to.clone()));
+        }
+        if room_size > 2 && rng.gen_bool(0.5) {
+            let pair: Vec<_> = room.choose_multiple(rng, 2).collect();
+            if !edges
+                .iter()
+                .any(|(a, b)| (a, b) == (pair[0], pair[1]) || (a, b) == (pair[1], pair[0]))
+            {
+                edges.push((pair[0].clone(), pair[1].clone()));
+            }
+        }
+
+        edges.push(("start".to_string(), room.choose(rng).unwrap().clone()));
+        edges.push((room.choose(rng).unwrap().clone(), "end".to_string()));
+    }
+    edges.shuffle(rng);
+
+    let mut out = String::new();
+    for (from, to) in edges {
+        writeln!(out, "{}-{}", from, to).unwrap();
+    }
+    out
+}
+
+/// The name of cave `idx`: `aa` to `zz`, then `aaa` and so on
+fn cave_name(idx: usize) -> String {
+    let (mut idx, mut length, mut count) = (idx, 2, 26 * 26);
+    while idx >= count {
+        idx -= count;
+        length += 1;
+        count *= 26;
+    }
+    let mut name = vec![b'a'; length];
+    for letter in name.iter_mut().rev() {
+        *letter += (idx % 26) as u8;
+        idx /= 26;
+    }
+    String::from_utf8(name).unwrap()
+}
+
+/// `size` dots on transparent paper, folded into a 40x6 code. Each fold is exactly in the middle
+/// of the paper, and there are always dots on the far edges of the paper and right next to each
+/// fold line.
+fn day13(rng: &mut StdRng, size: usize) -> String {
+    let (mut width, mut height) = (40, 6);
+    let mut dots: Vec<(usize, usize)> = (0..size)
+        .map(|_| (rng.gen_range(0..width), rng.gen_range(0..height)))
+        .collect();
+
+    // Unfold the paper, the last unfold is the first fold in the input
+    let mut folds = Vec::new();
+    let fold_count = 4 + (size as f64).log2() as usize / 2;
+    for _ in 0..fold_count {
+        if rng.gen_bool(0.5) {
+            for (x, _) in dots.iter_mut() {
+                if rng.gen_bool(0.5) {
+                    *x = 2 * width - *x;
+                }
+            }
+            dots.push((width + 1, 0));
+            folds.push(format!("fold along x={}", width));
+            width = 2 * width + 1;
+        } else {
+            for (_, y) in dots.iter_mut() {
+                if rng.gen_bool(0.5) {
+                    *y = 2 * height - *y;
+                }
+            }
+            dots.push((0, height + 1));
+            folds.push(format!("fold along y={}", height));
+            height = 2 * height + 1;
+        }
+    }
+    dots.push((width - 1, 0));
+    dots.push((0, height - 1));
+    dots.shuffle(rng);
+
+    let mut out = String::new();
+    for (x, y) in dots.into_iter().unique() {
+        writeln!(out, "{},{}", x, y).unwrap();
+    }
+    out.push('\n');
+    for fold in folds.iter().rev() {
+        writeln!(out, "{}", fold).unwrap();
+    }
+    out
+}
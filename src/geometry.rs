@@ -0,0 +1,501 @@
+use std::{
+    fmt::{self, Display},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// A location on the plane. `y` grows downwards, like the puzzles and the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// The difference between two points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Vector {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// The point mirrored in the line `axis = at`, e.g. folding along `x = 5` turns `(7, 1)`
+    /// into `(3, 1)`
+    pub fn reflect(self, axis: Axis, at: i64) -> Self {
+        match axis {
+            Axis::X => Self::new(2 * at - self.x, self.y),
+            Axis::Y => Self::new(self.x, 2 * at - self.y),
+        }
+    }
+}
+
+impl Vector {
+    pub const ZERO: Vector = Vector { x: 0, y: 0 };
+
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn dot(self, other: Vector) -> i64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The z component of the 3D cross product, 0 when the vectors are parallel
+    pub fn cross(self, other: Vector) -> i64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn manhattan(self) -> i64 {
+        self.x.abs() + self.y.abs()
+    }
+
+    /// The vector divided by the greatest common divisor of its components, so the smallest
+    /// step between grid points in its direction
+    pub fn primitive(self) -> Vector {
+        match gcd(self.x, self.y) {
+            0 => Vector::ZERO,
+            divisor => Vector::new(self.x / divisor, self.y / divisor),
+        }
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, vector: Vector) -> Point {
+        Point::new(self.x + vector.x, self.y + vector.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, vector: Vector) {
+        *self = *self + vector;
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+
+    fn sub(self, vector: Vector) -> Point {
+        Point::new(self.x - vector.x, self.y - vector.y)
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, vector: Vector) {
+        *self = *self - vector;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Point) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, factor: i64) -> Vector {
+        Vector::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Point> for sdl2::rect::Point {
+    fn from(point: Point) -> Self {
+        sdl2::rect::Point::new(point.x as i32, point.y as i32)
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// An axis-aligned bounding box, including both corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    /// The smallest box around all `points`, or `None` if there are none
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut aabb = Self {
+            min: first,
+            max: first,
+        };
+        points.for_each(|point| aabb.extend(point));
+        Some(aabb)
+    }
+
+    /// Grow the box so that it contains `point`
+    pub fn extend(&mut self, point: Point) {
+        self.min = Point::new(self.min.x.min(point.x), self.min.y.min(point.y));
+        self.max = Point::new(self.max.x.max(point.x), self.max.y.max(point.y));
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    /// The number of grid columns the box covers
+    pub fn width(&self) -> i64 {
+        self.max.x - self.min.x + 1
+    }
+
+    /// The number of grid rows the box covers
+    pub fn height(&self) -> i64 {
+        self.max.y - self.min.y + 1
+    }
+}
+
+/// A straight line between two points, including both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segment {
+    pub start: Point,
+    pub end: Point,
+}
+
+impl Segment {
+    pub fn new(start: Point, end: Point) -> Self {
+        Self { start, end }
+    }
+
+    pub fn direction(&self) -> Vector {
+        self.end - self.start
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x
+    }
+
+    /// Whether the segment is at 45 degrees
+    pub fn is_diagonal(&self) -> bool {
+        let direction = self.direction();
+        direction.x.abs() == direction.y.abs()
+    }
+
+    /// The grid cells a line from `start` to `end` is drawn with, with Bresenham's algorithm.
+    /// There is one per step along the longer axis, each touching the next at a side or a
+    /// corner, whatever the slope.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let direction = self.direction();
+        let (dx, dy) = (direction.x.abs(), -direction.y.abs());
+        let step = Vector::new(direction.x.signum(), direction.y.signum());
+        let mut point = self.start;
+        let mut error = dx + dy;
+        (0..=dx.max(-dy)).map(move |_| {
+            let current = point;
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                point.x += step.x;
+            }
+            if doubled <= dx {
+                error += dx;
+                point.y += step.y;
+            }
+            current
+        })
+    }
+
+    /// The grid points exactly on the segment, from `start` to `end`. For horizontal, vertical
+    /// and diagonal segments these are the same as `points`, other slopes skip cells.
+    pub fn lattice_points(&self) -> impl Iterator<Item = Point> {
+        let step = self.direction().primitive();
+        let steps = gcd(self.direction().x, self.direction().y);
+        let start = self.start;
+        (0..=steps).map(move |k| start + step * k)
+    }
+
+    /// Whether `point` is one of the segment's `lattice_points`
+    pub fn contains(&self, point: Point) -> bool {
+        let direction = self.direction();
+        if direction == Vector::ZERO {
+            return point == self.start;
+        }
+
+        let offset = point - self.start;
+        offset.cross(direction) == 0
+            && (0..=direction.dot(direction)).contains(&offset.dot(direction))
+    }
+
+    /// The grid points the two segments have in common, as a segment. That is a single point
+    /// where they cross, or the overlap when they lie on the same line.
+    pub fn intersection(&self, other: &Segment) -> Option<Segment> {
+        let (d1, d2) = (self.direction(), other.direction());
+
+        if d1 == Vector::ZERO {
+            return other
+                .contains(self.start)
+                .then(|| Segment::new(self.start, self.start));
+        }
+        if d2 == Vector::ZERO {
+            return other.intersection(self);
+        }
+
+        let offset = other.start - self.start;
+        let denominator = d1.cross(d2);
+        if denominator == 0 {
+            if offset.cross(d1) != 0 {
+                // Parallel, but not on the same line
+                return None;
+            }
+
+            // Both segments are on the same line, so all their points are `self.start` plus a
+            // multiple of the smallest step along it
+            let step = d1.primitive();
+            let steps_to = |point: Point| (point - self.start).dot(step) / step.dot(step);
+            let (a, b) = (steps_to(other.start), steps_to(other.end));
+            let from = a.min(b).max(0);
+            let to = a.max(b).min(steps_to(self.end));
+            return (from <= to)
+                .then(|| Segment::new(self.start + step * from, self.start + step * to));
+        }
+
+        // Solve `self.start + d1 * t == other.start + d2 * u` with `t` and `u` in 0..=1
+        let (t, u) = (offset.cross(d2), offset.cross(d1));
+        let in_range = |numerator: i64| {
+            if denominator > 0 {
+                (0..=denominator).contains(&numerator)
+            } else {
+                (denominator..=0).contains(&numerator)
+            }
+        };
+        if !in_range(t) || !in_range(u) {
+            return None;
+        }
+
+        // Only report crossings on a grid point
+        let (x, y) = (d1.x * t, d1.y * t);
+        if x % denominator != 0 || y % denominator != 0 {
+            return None;
+        }
+        let point = self.start + Vector::new(x / denominator, y / denominator);
+        Some(Segment::new(point, point))
+    }
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(x1: i64, y1: i64, x2: i64, y2: i64) -> Segment {
+        Segment::new(Point::new(x1, y1), Point::new(x2, y2))
+    }
+
+    fn point(x: i64, y: i64) -> Segment {
+        segment(x, y, x, y)
+    }
+
+    #[test]
+    fn crossing_segments_meet_in_a_point() {
+        assert_eq!(
+            segment(0, 2, 4, 2).intersection(&segment(1, 0, 1, 5)),
+            Some(point(1, 2))
+        );
+        assert_eq!(
+            segment(0, 0, 4, 4).intersection(&segment(0, 4, 4, 0)),
+            Some(point(2, 2))
+        );
+        // The diagonals of a 2 by 2 square cross between grid points
+        assert_eq!(segment(0, 0, 1, 1).intersection(&segment(0, 1, 1, 0)), None);
+        assert_eq!(
+            segment(0, 0, 2, 0).intersection(&segment(3, -1, 3, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn parallel_segments_dont_meet() {
+        assert_eq!(segment(0, 0, 5, 0).intersection(&segment(0, 1, 5, 1)), None);
+        assert_eq!(segment(0, 0, 3, 3).intersection(&segment(1, 0, 4, 3)), None);
+    }
+
+    #[test]
+    fn collinear_segments_meet_where_they_overlap() {
+        assert_eq!(
+            segment(0, 0, 5, 0).intersection(&segment(3, 0, 9, 0)),
+            Some(segment(3, 0, 5, 0))
+        );
+        // The overlap runs the way of the first segment
+        assert_eq!(
+            segment(5, 5, 0, 0).intersection(&segment(1, 1, 3, 3)),
+            Some(segment(3, 3, 1, 1))
+        );
+        assert_eq!(segment(0, 0, 2, 0).intersection(&segment(4, 0, 6, 0)), None);
+    }
+
+    #[test]
+    fn segments_touching_at_an_end_meet_there() {
+        assert_eq!(
+            segment(0, 0, 3, 0).intersection(&segment(3, 0, 3, 4)),
+            Some(point(3, 0))
+        );
+        assert_eq!(
+            segment(0, 0, 3, 0).intersection(&segment(3, 0, 6, 0)),
+            Some(point(3, 0))
+        );
+        assert_eq!(
+            point(2, 2).intersection(&segment(0, 0, 4, 4)),
+            Some(point(2, 2))
+        );
+        assert_eq!(
+            segment(0, 0, 4, 4).intersection(&point(2, 2)),
+            Some(point(2, 2))
+        );
+    }
+
+    #[test]
+    fn segments_contain_their_points() {
+        let diagonal = segment(1, 1, 4, 4);
+        assert!(diagonal.contains(Point::new(1, 1)));
+        assert!(diagonal.contains(Point::new(3, 3)));
+        assert!(diagonal.contains(Point::new(4, 4)));
+        assert!(!diagonal.contains(Point::new(0, 0)));
+        assert!(!diagonal.contains(Point::new(5, 5)));
+        assert!(!diagonal.contains(Point::new(2, 3)));
+
+        assert!(point(2, 2).contains(Point::new(2, 2)));
+        assert!(!point(2, 2).contains(Point::new(2, 3)));
+        assert!(!segment(0, 0, 3, 1).contains(Point::new(1, 0)));
+    }
+
+    fn points(segment: Segment) -> Vec<Point> {
+        segment.points().collect()
+    }
+
+    fn cells(cells: &[(i64, i64)]) -> Vec<Point> {
+        cells.iter().map(|&(x, y)| Point::new(x, y)).collect()
+    }
+
+    #[test]
+    fn straight_and_diagonal_points_are_the_lattice_points() {
+        for segment in [
+            segment(1, 1, 4, 4),
+            segment(4, 1, 1, 4),
+            segment(2, 5, 2, 0),
+            segment(0, 3, 6, 3),
+            point(2, 2),
+        ] {
+            assert_eq!(
+                points(segment),
+                segment.lattice_points().collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(
+            points(segment(1, 1, 3, 3)),
+            cells(&[(1, 1), (2, 2), (3, 3)])
+        );
+    }
+
+    #[test]
+    fn points_rasterise_any_slope() {
+        let shallow = segment(0, 0, 3, 1);
+        assert_eq!(points(shallow), cells(&[(0, 0), (1, 0), (2, 1), (3, 1)]));
+        // Only the ends are exactly on the line
+        assert_eq!(
+            shallow.lattice_points().collect::<Vec<_>>(),
+            cells(&[(0, 0), (3, 1)])
+        );
+
+        assert_eq!(
+            points(segment(0, 0, -1, -4)),
+            cells(&[(0, 0), (0, -1), (-1, -2), (-1, -3), (-1, -4)])
+        );
+
+        for segment in [
+            segment(0, 0, 7, 3),
+            segment(5, -2, -1, 9),
+            segment(3, 3, -4, 1),
+        ] {
+            let points = points(segment);
+            let direction = segment.direction();
+            assert_eq!(
+                points.len() as i64,
+                direction.x.abs().max(direction.y.abs()) + 1
+            );
+            assert_eq!(
+                (points[0], *points.last().unwrap()),
+                (segment.start, segment.end)
+            );
+            assert!(points.windows(2).all(|pair| {
+                let step = pair[1] - pair[0];
+                step != Vector::ZERO && step.x.abs() <= 1 && step.y.abs() <= 1
+            }));
+        }
+    }
+
+    #[test]
+    fn manhattan_distance_between_points() {
+        assert_eq!((Point::new(4, -2) - Point::new(1, 3)).manhattan(), 8);
+        assert_eq!((Point::new(1, 3) - Point::new(4, -2)).manhattan(), 8);
+        assert_eq!((Point::ORIGIN - Point::ORIGIN).manhattan(), 0);
+    }
+}
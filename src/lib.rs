@@ -0,0 +1,100 @@
+#![allow(dead_code)]
+
+use config::Config;
+use context::Context;
+use params::Params;
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    time::Duration,
+};
+use visualisation::{Output, WindowSettings};
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day9;
+
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+
+pub mod answers;
+pub mod check_input;
+pub mod config;
+pub mod context;
+pub mod fuzz;
+pub mod generate;
+pub mod geometry;
+pub mod params;
+pub mod parse;
+pub mod registry;
+pub mod report;
+pub mod scale;
+pub mod svg;
+pub mod util;
+pub mod visualisation;
+
+pub trait Solution {
+    const YEAR: u16;
+    const DAY: u8;
+    const PART: u8;
+    /// The `-p` parameters the solution understands, with a description of each
+    const PARAMS: &'static [(&'static str, &'static str)] = &[];
+
+    /// The answer, or why there is none, e.g. a parameter that can't be used
+    fn solve(buf: &mut impl BufRead, ctx: &Context) -> Result<String, String>;
+}
+
+pub trait Extra {
+    const YEAR: u16;
+    const DAY: u8;
+    /// What to call the extra with `-e`, unique within its day
+    const NAME: &'static str;
+    /// Whether to use a sample input when `-s` isn't given
+    const USE_SAMPLE: bool;
+    /// The parameters the extra understands, with a description of each
+    const PARAMS: &'static [(&'static str, &'static str)] = &[];
+
+    /// Run the extra, failing on parameters it can't use
+    fn run(buf: &mut impl BufRead, window: &WindowSettings, params: &Params) -> Result<(), String>;
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:>8.3}ms", duration.as_secs_f64() * 1000f64)
+}
+
+fn extra<E: Extra>(
+    config: &Config,
+    sample: Option<&str>,
+    params: &Params,
+    output: &Output,
+) -> Result<(), String> {
+    let extra_config = config.extra(E::DAY, E::NAME);
+    let use_sample = extra_config
+        .use_sample
+        .map_or(E::USE_SAMPLE, |setting| setting.value);
+    let sample = match sample {
+        Some(sample) => Some(sample),
+        None if use_sample => Some(config.sample.value.as_str()),
+        None => None,
+    };
+
+    let filepath = config.input_path(E::YEAR, E::DAY, sample);
+    let file = fs::File::open(filepath).expect("Could not read file");
+
+    E::run(
+        &mut BufReader::new(file),
+        &WindowSettings {
+            font: config.font.value.clone(),
+            fps: extra_config.fps.map(|setting| setting.value),
+            output: output.clone(),
+        },
+        params,
+    )
+}
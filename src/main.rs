@@ -1,227 +1,451 @@
-#![allow(dead_code)]
-
-use clap::Parser;
+use aoc21::{
+    check_input,
+    config::{Config, Setting, Source},
+    context::{Context, Level},
+    format_duration, fuzz, generate,
+    params::Params,
+    registry::{self, registry, Implementation},
+    report, scale,
+    visualisation::{export::FrameRange, Output},
+};
+use clap::{Parser, Subcommand};
 use std::{
     fs,
-    io::{BufRead, BufReader},
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day9;
-
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-
-mod util;
-mod visualisation;
-
-trait Solution {
-    const DAY: u8;
-    const PART: u8;
-
-    fn solve(buf: &mut impl BufRead) -> String;
-}
-
-trait Extra {
-    const DAY: u8;
-    const USE_SAMPLE: bool;
-
-    fn run(buf: &mut impl BufRead);
-}
-
-fn format_duration(duration: Duration) -> String {
-    format!("{:>8.3}ms", duration.as_secs_f64() * 1000f64)
-}
-
-fn solution_with_file<S: Solution>(filepath: &String) -> Duration {
-    let file = fs::File::open(filepath).expect("Could not read file");
+fn solution(
+    implementation: &Implementation,
+    config: &Config,
+    sample: Option<&str>,
+    ctx: &Context,
+) -> Duration {
+    let filepath = config.input_path(implementation.year, implementation.day, sample);
+    let input = fs::read_to_string(filepath).expect("Could not read file");
 
     let before = Instant::now();
-    let answer = S::solve(&mut BufReader::new(file));
+    let answer = (implementation.solve)(&input, ctx);
     let after = Instant::now();
 
     let duration = after - before;
+    let answer = answer.unwrap_or_else(|e| {
+        eprintln!(
+            "[Day {:>2}][Part {}]: {}",
+            implementation.day, implementation.part, e
+        );
+        std::process::exit(1);
+    });
 
     println!(
         "[{}][Day {:>2}][Part {}]: {}",
         format_duration(duration),
-        S::DAY,
-        S::PART,
+        implementation.day,
+        implementation.part,
         answer
     );
 
     duration
 }
 
-fn solution<S: Solution>(use_sample: &Option<String>) -> Duration {
-    if let Some(sample) = use_sample {
-        if sample.is_empty() {
-            solution_with_file::<S>(&format!("inputs/{}_sample", S::DAY))
-        } else {
-            solution_with_file::<S>(&format!("inputs/{}_sample_{}", S::DAY, sample))
+fn list(year: u16) {
+    for day in registry().iter().filter(|d| d.year == year) {
+        println!("Day {:>2}", day.day);
+        for implementation in &day.implementations {
+            println!("  part {}  {}", implementation.part, implementation.name);
+            for (name, description) in implementation.params {
+                println!("          -p {}=...  {}", name, description);
+            }
+        }
+        for (idx, extra) in day.extras.iter().enumerate() {
+            println!(
+                "  -e {}{}",
+                extra.name,
+                if idx == 0 { " (default)" } else { "" }
+            );
+            for (name, description) in extra.params {
+                println!("       -p {}=...  {}", name, description);
+            }
         }
-    } else {
-        solution_with_file::<S>(&format!("inputs/{}", S::DAY))
     }
 }
 
-fn extra<E: Extra>() {
-    let filepath = if E::USE_SAMPLE {
-        format!("inputs/{}_sample", E::DAY)
-    } else {
-        format!("inputs/{}", E::DAY)
-    };
-
-    let file = fs::File::open(filepath).expect("Could not read file");
-
-    E::run(&mut BufReader::new(file));
-}
-
 #[derive(Parser)]
 struct Opts {
     #[clap()]
-    day: Option<i32>,
-
-    #[clap(short, about = "Run an 'extra', e.g. a visualisation")]
-    extra: bool,
+    day: Option<u8>,
+
+    #[clap(long, default_value = "2021", about = "Which year's solutions to use")]
+    year: u16,
+
+    #[clap(
+        short,
+        about = "Run an 'extra', e.g. a visualisation, by default the day's first one"
+    )]
+    extra: Option<Option<String>>,
+
+    #[clap(
+        short = 's',
+        require_equals = true,
+        about = "Use a sample input, by default the one in the configuration, or -s=<name>"
+    )]
+    sample_input: Option<Option<String>>,
+
+    #[clap(long, about = "Directory to read inputs from")]
+    inputs: Option<PathBuf>,
+
+    #[clap(long, about = "TTF font to use in visualisations")]
+    font: Option<PathBuf>,
+
+    #[clap(
+        long,
+        about = "Frames per second of visualisations, 0 for as fast as possible"
+    )]
+    fps: Option<u32>,
+
+    #[clap(
+        long,
+        about = "Render this many frames of a visualisation offscreen instead of in a window"
+    )]
+    headless: Option<usize>,
+
+    #[clap(
+        long,
+        conflicts_with = "headless",
+        about = "Show visualisations in the terminal instead of in a window"
+    )]
+    term: bool,
+
+    #[clap(
+        long,
+        about = "Write the frames of a visualisation to this directory as numbered PNG files"
+    )]
+    export_frames: Option<PathBuf>,
+
+    #[clap(
+        long,
+        about = "Write the frames of a visualisation to this file as an animated GIF"
+    )]
+    gif: Option<PathBuf>,
+
+    #[clap(
+        long,
+        about = "Milliseconds each frame of the GIF shows, by default as long as in the window"
+    )]
+    gif_delay: Option<u64>,
+
+    #[clap(
+        long,
+        about = "Write the frames of a visualisation to this file as a YUV4MPEG2 video, - for stdout"
+    )]
+    y4m: Option<PathBuf>,
+
+    #[clap(
+        long,
+        default_value = "1",
+        about = "Make exported frames this many times smaller"
+    )]
+    downscale: u32,
+
+    #[clap(long, default_value = "0", about = "The first frame to export")]
+    first_frame: usize,
+
+    #[clap(long, about = "The last frame to export, by default all of them")]
+    last_frame: Option<usize>,
+
+    #[clap(long, default_value = "1", about = "Export every Nth frame")]
+    frame_step: usize,
+
+    #[clap(
+        short,
+        long = "param",
+        about = "A key=value parameter for a day or an extra, e.g. -p cell_size=20"
+    )]
+    params: Vec<String>,
+
+    #[clap(
+        short,
+        long,
+        parse(from_occurrences),
+        about = "Log what solutions are doing to stderr, repeat for more detail"
+    )]
+    verbose: u64,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
 
-    #[clap(short = 's', about = "Use a sample input")]
-    sample_input: Option<String>,
+#[derive(Subcommand)]
+enum Command {
+    #[clap(about = "Generate a random input for a day and write it to stdout")]
+    Gen {
+        day: u8,
+
+        #[clap(
+            long,
+            default_value = "100",
+            about = "How big the input should be",
+            long_about = generate::SIZE_HELP
+        )]
+        size: usize,
+
+        #[clap(long, default_value = "0", about = "Seed for the random generator")]
+        seed: u64,
+    },
+
+    #[clap(about = "Compare all implementations of a day's parts on random inputs")]
+    Fuzz {
+        day: u8,
+
+        #[clap(long, default_value = "1000", about = "How many inputs to try")]
+        iterations: usize,
+
+        #[clap(
+            long,
+            default_value = "10",
+            about = "The largest input size to try",
+            long_about = generate::SIZE_HELP
+        )]
+        size: usize,
+
+        #[clap(long, default_value = "0", about = "Seed for the random generator")]
+        seed: u64,
+    },
+
+    #[clap(about = "List the days with their implementations and extras")]
+    List,
+
+    #[clap(about = "Check that an input looks like it should for its day")]
+    CheckInput {
+        day: u8,
+
+        #[clap(
+            long,
+            about = "Fix line endings, trailing whitespace and the final newline in place"
+        )]
+        fix: bool,
+    },
+
+    #[clap(about = "Print the effective configuration and where each value came from")]
+    Config,
+
+    #[clap(about = "Run everything and write a report of the answers and timings")]
+    Report {
+        #[clap(long, arg_enum, default_value = "markdown")]
+        format: report::Format,
+
+        #[clap(
+            short,
+            long,
+            about = "Where to write the report, by default report.md or report.html"
+        )]
+        output: Option<PathBuf>,
+    },
+
+    #[clap(about = "Measure how a day's running time grows with the size of its input")]
+    Scale {
+        day: u8,
+
+        #[clap(long, about = "Only measure this part")]
+        part: Option<u8>,
+
+        #[clap(long, default_value = "8", about = "The smallest input size")]
+        start: usize,
+
+        #[clap(
+            long,
+            default_value = "2",
+            about = "How much the input size grows each step"
+        )]
+        factor: f64,
+
+        #[clap(long, default_value = "12", about = "The most input sizes to measure")]
+        steps: usize,
+
+        #[clap(
+            long,
+            default_value = "1000",
+            about = "Stop once a single run takes longer than this many milliseconds"
+        )]
+        budget: u64,
+
+        #[clap(long, default_value = "0", about = "Seed for the random generator")]
+        seed: u64,
+    },
 }
 
 fn main() {
     let opts = Opts::parse();
 
-    match opts.day {
-        None => {
-            let mut clock: Duration = Duration::new(0, 0);
-
-            clock += solution::<day1::Part1>(&opts.sample_input);
-            clock += solution::<day1::Part2>(&opts.sample_input);
-
-            clock += solution::<day2::Part1>(&opts.sample_input);
-            clock += solution::<day2::Part2>(&opts.sample_input);
-
-            clock += solution::<day3::Part1>(&opts.sample_input);
-            clock += solution::<day3::Part2>(&opts.sample_input);
-
-            clock += solution::<day4::Part1>(&opts.sample_input);
-            clock += solution::<day4::Part2>(&opts.sample_input);
-
-            clock += solution::<day5::Part1<1024>>(&opts.sample_input);
-            clock += solution::<day5::Part2<1024>>(&opts.sample_input);
-
-            clock += solution::<day6::Part1>(&opts.sample_input);
-            clock += solution::<day6::Part2>(&opts.sample_input);
-
-            clock += solution::<day7::Part1>(&opts.sample_input);
-            clock += solution::<day7::Part2>(&opts.sample_input);
-
-            clock += solution::<day9::Part1>(&opts.sample_input);
-            clock += solution::<day9::Part2>(&opts.sample_input);
-
-            clock += solution::<day10::Part1>(&opts.sample_input);
-            clock += solution::<day10::Part2>(&opts.sample_input);
-
-            clock += solution::<day11::Part1>(&opts.sample_input);
-            clock += solution::<day11::Part2>(&opts.sample_input);
-
-            clock += solution::<day12::Part1>(&opts.sample_input);
-            clock += solution::<day12::Part2>(&opts.sample_input);
-
-            clock += solution::<day13::Part1>(&opts.sample_input);
-            clock += solution::<day13::Part2>(&opts.sample_input);
-
-            println!("[{}]", format_duration(clock));
-        }
-
-        Some(1) => {
-            solution::<day1::Part1>(&opts.sample_input);
-            solution::<day1::Part2>(&opts.sample_input);
-        }
-
-        Some(2) => {
-            solution::<day2::Part1>(&opts.sample_input);
-            solution::<day2::Part2>(&opts.sample_input);
-        }
-
-        Some(3) => {
-            solution::<day3::Part1>(&opts.sample_input);
-            solution::<day3::Part2>(&opts.sample_input);
-        }
-
-        Some(4) => {
-            if opts.extra {
-                extra::<day4::Visualise>();
-            } else {
-                solution::<day4::Part1>(&opts.sample_input);
-                solution::<day4::Part2>(&opts.sample_input);
+    let mut config = Config::load().unwrap_or_else(|e| {
+        eprintln!("Could not load the configuration: {}", e);
+        std::process::exit(1);
+    });
+    config.inputs.update(opts.inputs, Source::Cli);
+    config.font.update(opts.font, Source::Cli);
+    if let Some(fps) = opts.fps {
+        for day in registry() {
+            for extra in &day.extras {
+                config.extra_mut(day.day, extra.name).fps = Some(Setting::new(fps, Source::Cli));
             }
         }
-
-        Some(5) => {
-            solution::<day5::Part1<1024>>(&opts.sample_input);
-            solution::<day5::Part2<1024>>(&opts.sample_input);
-        }
-
-        Some(6) => {
-            solution::<day6::Part1>(&opts.sample_input);
-            solution::<day6::Part2>(&opts.sample_input);
+    }
+    config
+        .sample
+        .update(opts.sample_input.clone().flatten(), Source::Cli);
+    let sample = opts
+        .sample_input
+        .is_some()
+        .then(|| config.sample.value.as_str());
+    let params = Params::parse(opts.params.iter().map(String::as_str)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let check_params = |checked: Result<(), String>| {
+        if let Err(e) = checked {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
+    };
 
-        Some(7) => {
-            if opts.extra {
-                extra::<day7::Visualise>();
-            } else {
-                solution::<day7::Part1>(&opts.sample_input);
-                solution::<day7::Part2>(&opts.sample_input);
+    if let Some(command) = opts.command {
+        let find_day = |day| {
+            registry::find(opts.year, day).unwrap_or_else(|| {
+                println!("I have no solution for day {} of {}", day, opts.year);
+                std::process::exit(1);
+            })
+        };
+
+        match command {
+            Command::Config => config.print(
+                &registry()
+                    .iter()
+                    .filter(|d| d.year == opts.year)
+                    .flat_map(|d| d.extras.iter().map(|e| (d.day, e.name, e.use_sample)))
+                    .collect::<Vec<_>>(),
+            ),
+            Command::List => list(opts.year),
+            Command::CheckInput { day, fix } => {
+                let path = config.input_path(opts.year, day, sample);
+                match check_input::check_input(opts.year, day, &path, fix) {
+                    Ok(()) => println!("{} looks like an input for day {}", path.display(), day),
+                    Err(e) => {
+                        println!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            Command::Gen { day, size, seed } => {
+                match generate::generate(opts.year, day, size, seed) {
+                    Some(input) => print!("{}", input),
+                    None => println!("I have no input generator for day {} of {}", day, opts.year),
+                }
             }
+            Command::Fuzz {
+                day,
+                iterations,
+                size,
+                seed,
+            } => fuzz::fuzz(&config, &find_day(day), iterations, size, seed),
+            Command::Report { format, output } => {
+                let output = output
+                    .unwrap_or_else(|| PathBuf::from(format!("report.{}", format.extension())));
+                match report::report(&config, opts.year, sample, format, &output) {
+                    Ok(()) => println!("Wrote the report to {}", output.display()),
+                    Err(e) => {
+                        eprintln!("Report failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            Command::Scale {
+                day,
+                part,
+                start,
+                factor,
+                steps,
+                budget,
+                seed,
+            } => scale::scale(
+                &find_day(day),
+                part,
+                &scale::Options {
+                    start,
+                    factor,
+                    steps,
+                    budget: Duration::from_millis(budget),
+                    seed,
+                },
+            ),
         }
+        return;
+    }
 
-        Some(9) => {
-            if opts.extra {
-                extra::<day9::Progression>();
-            } else {
-                solution::<day9::Part1>(&opts.sample_input);
-                solution::<day9::Part2>(&opts.sample_input);
+    match opts.day {
+        None => {
+            let days: Vec<_> = registry()
+                .into_iter()
+                .filter(|d| d.year == opts.year)
+                .collect();
+            if days.is_empty() {
+                println!("I have no solutions for {}", opts.year);
+                return;
             }
-        }
 
-        Some(10) => {
-            solution::<day10::Part1>(&opts.sample_input);
-            solution::<day10::Part2>(&opts.sample_input);
-        }
+            check_params(registry::check_params(&days, &params));
+            let ctx = Context::new(Level::from_verbosity(opts.verbose), params);
+            let mut clock: Duration = Duration::new(0, 0);
 
-        Some(11) => {
-            if opts.extra {
-                extra::<day11::Octoblink>();
-            } else {
-                solution::<day11::Part1>(&opts.sample_input);
-                solution::<day11::Part2>(&opts.sample_input);
+            for day in &days {
+                for implementation in day.parts() {
+                    clock += solution(implementation, &config, sample, &ctx);
+                }
             }
-        }
-
-        Some(12) => {
-            solution::<day12::Part1>(&opts.sample_input);
-            solution::<day12::Part2>(&opts.sample_input);
-        }
 
-        Some(13) => {
-            solution::<day13::Part1>(&opts.sample_input);
-            solution::<day13::Part2>(&opts.sample_input);
+            println!("[{}]", format_duration(clock));
         }
 
-        Some(n) => {
-            println!("I have no solution for day {}", n);
-        }
+        Some(n) => match registry::find(opts.year, n) {
+            None => println!("I have no solution for day {} of {}", n, opts.year),
+            Some(day) if opts.extra.is_some() => {
+                match day.find_extra(opts.extra.clone().flatten().as_deref()) {
+                    Some(extra) => {
+                        check_params(extra.check_params(&params));
+                        let output = Output {
+                            headless: opts.headless,
+                            term: opts.term,
+                            export_frames: opts.export_frames.clone(),
+                            gif: opts.gif.clone(),
+                            gif_delay: opts.gif_delay.map(Duration::from_millis),
+                            y4m: opts.y4m.clone(),
+                            frames: FrameRange {
+                                first: opts.first_frame,
+                                last: opts.last_frame,
+                                step: opts.frame_step,
+                            },
+                            downscale: opts.downscale,
+                        };
+                        if let Err(e) = (extra.run)(&config, sample, &params, &output) {
+                            eprintln!("{}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                    None => match opts.extra.flatten() {
+                        None => println!("I have no extra for day {} of {}", n, opts.year),
+                        Some(name) => println!(
+                            "I have no extra called {} for day {} of {}",
+                            name, n, opts.year
+                        ),
+                    },
+                }
+            }
+            Some(day) => {
+                check_params(day.check_params(&params));
+                let ctx = Context::new(Level::from_verbosity(opts.verbose), params);
+                for implementation in day.parts() {
+                    solution(implementation, &config, sample, &ctx);
+                }
+            }
+        },
     };
 }
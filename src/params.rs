@@ -0,0 +1,65 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+/// `key=value` parameters given on the command line, e.g. the cell size of a visualisation
+#[derive(Debug, Default, Clone)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn parse<'a>(args: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut params = BTreeMap::new();
+        for arg in args {
+            let (key, value) = arg
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' is not of the form key=value", arg))?;
+            params.insert(key.trim().to_string(), value.trim().to_string());
+        }
+        Ok(Self(params))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Check that all parameters are among `known`, which belong to `owner`, e.g. "this extra"
+    pub fn check(&self, known: &[(&str, &str)], owner: &str) -> Result<(), String> {
+        for key in self.keys() {
+            if !known.iter().any(|(name, _)| *name == key) {
+                let known: Vec<_> = known.iter().map(|(name, _)| *name).collect();
+                return Err(format!(
+                    "Unknown parameter '{}', {} has: {}",
+                    key,
+                    owner,
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The value of `key`, if it was given, or an error if it isn't a valid `T`
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, String>
+    where
+        T::Err: Display,
+    {
+        self.0
+            .get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e| format!("Invalid value '{}' for {}: {}", value, key, e))
+            })
+            .transpose()
+    }
+
+    /// The value of `key`, or `default` if it wasn't given
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, String>
+    where
+        T::Err: Display,
+    {
+        Ok(self.get(key)?.unwrap_or(default))
+    }
+}
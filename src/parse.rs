@@ -0,0 +1,184 @@
+use std::{fmt, io::BufRead, str::FromStr};
+
+/// Why an input could not be parsed, and on which line
+pub struct ParseError {
+    /// 1-based, relative to the start of the parsed text
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// Solutions `unwrap` parse results, so make that print the same as `Display`
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// The whole input
+pub fn read_to_string(buf: &mut impl BufRead) -> String {
+    let mut text = String::new();
+    buf.read_to_string(&mut text).expect("Could not read input");
+    text
+}
+
+/// Parse a single value, e.g. a number
+pub fn value<T: FromStr>(text: &str) -> Result<T, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("expected a number, found '{}'", text))
+}
+
+/// Values separated by `separator`, e.g. `3,4,3,1,2`
+pub fn separated<T: FromStr>(line: &str, separator: char) -> Result<Vec<T>, String> {
+    line.split(separator).map(value).collect()
+}
+
+/// Values separated by any amount of whitespace, e.g. a row of a bingo board
+pub fn whitespace_separated<T: FromStr>(line: &str) -> Result<Vec<T>, String> {
+    line.split_whitespace().map(value).collect()
+}
+
+pub type Point<T> = (T, T);
+
+/// `x,y`
+pub fn point<T: FromStr>(text: &str) -> Result<Point<T>, String> {
+    match text.split_once(',') {
+        Some((x, y)) => Ok((value(x)?, value(y)?)),
+        None => Err(format!("expected a point like '3,4', found '{}'", text)),
+    }
+}
+
+/// `x,y -> x,y`
+pub fn point_pair<T: FromStr>(line: &str) -> Result<(Point<T>, Point<T>), String> {
+    match line.split_once(" -> ") {
+        Some((start, end)) => Ok((point(start)?, point(end)?)),
+        None => Err(format!(
+            "expected a line segment like '0,9 -> 5,9', found '{}'",
+            line
+        )),
+    }
+}
+
+/// Parse every line of `text` with `parse_line`. Empty lines are skipped.
+pub fn lines<T>(
+    text: &str,
+    parse_line: impl Fn(&str) -> Result<T, String>,
+) -> Result<Vec<T>, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(idx, line)| parse_line(line).map_err(|e| ParseError::new(idx + 1, e)))
+        .collect()
+}
+
+/// An input that is a single line of comma-separated values
+pub fn comma_separated<T: FromStr>(text: &str) -> Result<Vec<T>, ParseError> {
+    let mut values = lines(text, |line| separated(line, ','))?;
+    match values.len() {
+        1 => Ok(values.remove(0)),
+        0 => Err(ParseError::new(1, "the input is empty")),
+        _ => Err(ParseError::new(
+            2,
+            "expected a single line of comma-separated values",
+        )),
+    }
+}
+
+/// A rectangle of single digits, row by row
+pub fn digit_grid<T: TryFrom<u8>>(text: &str) -> Result<Vec<Vec<T>>, ParseError> {
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    for (idx, line) in text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+    {
+        let row = line
+            .chars()
+            .map(
+                |c| match c.to_digit(10).and_then(|d| T::try_from(d as u8).ok()) {
+                    Some(digit) => Ok(digit),
+                    None => Err(ParseError::new(
+                        idx + 1,
+                        format!("expected a digit, found '{}'", c),
+                    )),
+                },
+            )
+            .collect::<Result<Vec<T>, ParseError>>()?;
+
+        if let Some(first) = rows.first().filter(|first| first.len() != row.len()) {
+            return Err(ParseError::new(
+                idx + 1,
+                format!(
+                    "expected {} digits like the first row, found {}",
+                    first.len(),
+                    row.len()
+                ),
+            ));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// A block of lines in an input, separated from the others by blank lines
+pub struct Section<'a> {
+    /// The line the section starts on, 1-based
+    pub line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    /// Parse the section, reporting errors with line numbers of the whole input
+    pub fn parse<T>(
+        &self,
+        parse: impl FnOnce(&'a str) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        parse(self.text).map_err(|e| ParseError::new(self.line + e.line - 1, e.message))
+    }
+}
+
+/// The sections of `text`, which are separated by one or more blank lines
+pub fn sections(text: &str) -> Vec<Section<'_>> {
+    let mut sections = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (idx, line) in text.split_inclusive('\n').enumerate() {
+        let blank = line.trim_end().is_empty();
+        match start {
+            None if !blank => start = Some((idx + 1, offset)),
+            Some((line, start_offset)) if blank => {
+                sections.push(Section {
+                    line,
+                    text: &text[start_offset..offset],
+                });
+                start = None;
+            }
+            _ => (),
+        }
+        offset += line.len();
+    }
+
+    if let Some((line, start_offset)) = start {
+        sections.push(Section {
+            line,
+            text: &text[start_offset..],
+        });
+    }
+    sections
+}
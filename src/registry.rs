@@ -0,0 +1,197 @@
+use std::any::type_name;
+
+use crate::{
+    config::Config, context::Context, day1, day10, day11, day12, day13, day2, day3, day4, day5,
+    day6, day7, day9, extra, params::Params, svg::Svg, visualisation::Output, Extra, Solution,
+};
+
+/// The year solutions are for when no year is given
+pub const DEFAULT_YEAR: u16 = 2021;
+
+/// One way of solving a part of a day. A part can have several implementations, e.g. a naive
+/// one and an optimised one, which should always give the same answer.
+pub struct Implementation {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub name: &'static str,
+    /// The parameters the implementation understands, with a description of each
+    pub params: &'static [(&'static str, &'static str)],
+    pub solve: fn(&str, &Context) -> Result<String, String>,
+}
+
+impl Implementation {
+    fn of<S: Solution>() -> Self {
+        Self {
+            year: S::YEAR,
+            day: S::DAY,
+            part: S::PART,
+            name: type_name::<S>().rsplit("::").next().unwrap(),
+            params: S::PARAMS,
+            solve: |input, ctx| S::solve(&mut input.as_bytes(), ctx),
+        }
+    }
+}
+
+pub struct RegisteredExtra {
+    pub name: &'static str,
+    /// Whether the extra uses a sample input unless configured otherwise
+    pub use_sample: bool,
+    /// The parameters the extra understands, with a description of each
+    pub params: &'static [(&'static str, &'static str)],
+    pub run: fn(&Config, Option<&str>, &Params, &Output) -> Result<(), String>,
+}
+
+impl RegisteredExtra {
+    /// Check that the extra understands all of `params`
+    pub fn check_params(&self, params: &Params) -> Result<(), String> {
+        params.check(self.params, "this extra")
+    }
+}
+
+/// Everything there is for one day of one year
+pub struct Day {
+    pub year: u16,
+    pub day: u8,
+    /// All implementations of the day's parts. The first implementation of each part is the one
+    /// that gets run, the rest are alternatives to compare it against.
+    pub implementations: Vec<Implementation>,
+    /// The day's extras. The first one is the default, run by `-e` without a name.
+    pub extras: Vec<RegisteredExtra>,
+    /// Draws a picture of the day's input, e.g. for reports
+    pub render: Option<fn(&str) -> Svg>,
+}
+
+impl Day {
+    fn new(year: u16, day: u8) -> Self {
+        Self {
+            year,
+            day,
+            implementations: Vec::new(),
+            extras: Vec::new(),
+            render: None,
+        }
+    }
+
+    fn part<S: Solution>(mut self) -> Self {
+        assert_eq!((S::YEAR, S::DAY), (self.year, self.day));
+        self.implementations.push(Implementation::of::<S>());
+        self
+    }
+
+    fn extra<E: Extra>(mut self) -> Self {
+        assert_eq!((E::YEAR, E::DAY), (self.year, self.day));
+        assert!(
+            self.find_extra(Some(E::NAME)).is_none(),
+            "Duplicate extra {}",
+            E::NAME
+        );
+        self.extras.push(RegisteredExtra {
+            name: E::NAME,
+            use_sample: E::USE_SAMPLE,
+            params: E::PARAMS,
+            run: extra::<E>,
+        });
+        self
+    }
+
+    fn render(mut self, render: fn(&str) -> Svg) -> Self {
+        self.render = Some(render);
+        self
+    }
+
+    /// The extra called `name`, or the default one if there is no name
+    pub fn find_extra(&self, name: Option<&str>) -> Option<&RegisteredExtra> {
+        match name {
+            None => self.extras.first(),
+            Some(name) => self.extras.iter().find(|e| e.name == name),
+        }
+    }
+
+    /// The implementation that gets run for each part
+    pub fn parts(&self) -> impl Iterator<Item = &Implementation> {
+        (1..=2).filter_map(|part| self.implementations.iter().find(|i| i.part == part))
+    }
+
+    /// Check that the parts that get run understand all of `params`. A parameter only needs to
+    /// be understood by one of them.
+    pub fn check_params(&self, params: &Params) -> Result<(), String> {
+        params.check(&known_params([self]), "this day")
+    }
+}
+
+/// Check that the parts of `days` that get run understand all of `params`, when running them
+/// all. A parameter only needs to be understood by one of them.
+pub fn check_params(days: &[Day], params: &Params) -> Result<(), String> {
+    params.check(&known_params(days), "any of these days")
+}
+
+/// The parameters the parts of `days` that get run understand, each once
+fn known_params<'a>(days: impl IntoIterator<Item = &'a Day>) -> Vec<(&'static str, &'static str)> {
+    let mut known: Vec<_> = days
+        .into_iter()
+        .flat_map(Day::parts)
+        .flat_map(|implementation| implementation.params.iter().copied())
+        .collect();
+    known.sort_by_key(|(name, _)| *name);
+    known.dedup_by_key(|(name, _)| *name);
+    known
+}
+
+pub fn registry() -> Vec<Day> {
+    vec![
+        Day::new(2021, 1)
+            .part::<day1::Part1>()
+            .part::<day1::Part2>(),
+        Day::new(2021, 2)
+            .part::<day2::Part1>()
+            .part::<day2::Part2>(),
+        Day::new(2021, 3)
+            .part::<day3::Part1>()
+            .part::<day3::Part2>(),
+        Day::new(2021, 4)
+            .part::<day4::Part1>()
+            .part::<day4::Part2>()
+            .extra::<day4::Visualise>(),
+        Day::new(2021, 5)
+            .part::<day5::Part1<1024>>()
+            .part::<day5::Part2<1024>>(),
+        Day::new(2021, 6)
+            .part::<day6::Part1>()
+            .part::<day6::Part1Naive>()
+            .part::<day6::Part2>(),
+        Day::new(2021, 7)
+            .part::<day7::Part1>()
+            .part::<day7::Part2>()
+            .part::<day7::Part2Mean>()
+            .extra::<day7::Visualise>(),
+        Day::new(2021, 9)
+            .part::<day9::Part1>()
+            .part::<day9::Part2>()
+            .part::<day9::Part2Flood>()
+            .extra::<day9::Progression>()
+            .extra::<day9::Heightmap>()
+            .render(|input| day9::render(&mut input.as_bytes())),
+        Day::new(2021, 10)
+            .part::<day10::Part1>()
+            .part::<day10::Part2>(),
+        Day::new(2021, 11)
+            .part::<day11::Part1>()
+            .part::<day11::Part2>()
+            .extra::<day11::Octoblink>(),
+        Day::new(2021, 12)
+            .part::<day12::Part1>()
+            .part::<day12::Part2>(),
+        Day::new(2021, 13)
+            .part::<day13::Part1>()
+            .part::<day13::Part2>()
+            .render(|input| day13::render(&mut input.as_bytes())),
+    ]
+}
+
+/// The registered day `day` of `year`, if there is one
+pub fn find(year: u16, day: u8) -> Option<Day> {
+    registry()
+        .into_iter()
+        .find(|d| d.year == year && d.day == day)
+}
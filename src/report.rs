@@ -0,0 +1,381 @@
+use std::{
+    fmt::Write,
+    fs, panic,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use clap::ArgEnum;
+
+use crate::{
+    answers::{Answers, Verification},
+    config::Config,
+    context::Context,
+    format_duration,
+    registry::registry,
+};
+
+#[derive(ArgEnum, Clone, Copy)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Html => "html",
+        }
+    }
+}
+
+enum Outcome {
+    Answer(String, Verification),
+    /// The solution gave an error rather than an answer
+    Failed(String),
+    Panicked,
+    NoInput,
+}
+
+struct PartResult {
+    day: u8,
+    part: u8,
+    outcome: Outcome,
+    duration: Duration,
+}
+
+struct Render {
+    day: u8,
+    data_uri: String,
+}
+
+struct Report {
+    year: u16,
+    input_description: String,
+    parts: Vec<PartResult>,
+    renders: Vec<Render>,
+}
+
+impl Report {
+    fn total(&self) -> Duration {
+        self.parts.iter().map(|p| p.duration).sum()
+    }
+
+    fn slowest(&self) -> Duration {
+        self.parts
+            .iter()
+            .map(|p| p.duration)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The slowest parts, slowest first
+    fn ranking(&self) -> Vec<&PartResult> {
+        let mut ranking: Vec<&PartResult> = self
+            .parts
+            .iter()
+            .filter(|p| matches!(p.outcome, Outcome::Answer(..)))
+            .collect();
+        ranking.sort_by_key(|p| std::cmp::Reverse(p.duration));
+        ranking.truncate(5);
+        ranking
+    }
+}
+
+fn status(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Answer(_, Verification::Correct) => "✅ correct".to_string(),
+        Outcome::Answer(_, Verification::Wrong { expected }) => {
+            format!("❌ expected {}", expected.trim())
+        }
+        Outcome::Answer(_, Verification::Unknown) => "❔ unknown".to_string(),
+        Outcome::Failed(e) => format!("💥 {}", e),
+        Outcome::Panicked => "💥 panicked".to_string(),
+        Outcome::NoInput => "no input".to_string(),
+    }
+}
+
+fn run(config: &Config, year: u16, sample: Option<&str>) -> Result<Report, String> {
+    let answers = Answers::load(config, year)?;
+    let input_name = |day| Config::input_name(day, sample);
+
+    // Panics are reported in the table
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut parts = Vec::new();
+    let mut renders = Vec::new();
+    for day in registry().iter().filter(|d| d.year == year) {
+        let input = fs::read_to_string(config.input_path(year, day.day, sample)).ok();
+
+        for implementation in day.parts() {
+            let mut duration = Duration::default();
+            let outcome = match &input {
+                None => Outcome::NoInput,
+                Some(input) => {
+                    let before = Instant::now();
+                    let answer =
+                        panic::catch_unwind(|| (implementation.solve)(input, &Context::default()));
+                    duration = Instant::now() - before;
+
+                    match answer {
+                        Ok(Ok(answer)) => {
+                            let verification =
+                                answers.verify(&input_name(day.day), implementation.part, &answer);
+                            Outcome::Answer(answer, verification)
+                        }
+                        Ok(Err(e)) => Outcome::Failed(e),
+                        Err(_) => Outcome::Panicked,
+                    }
+                }
+            };
+
+            parts.push(PartResult {
+                day: day.day,
+                part: implementation.part,
+                outcome,
+                duration,
+            });
+        }
+
+        if let (Some(render), Some(input)) = (day.render, &input) {
+            if let Ok(svg) = panic::catch_unwind(|| render(input)) {
+                renders.push(Render {
+                    day: day.day,
+                    data_uri: svg.to_data_uri(),
+                });
+            }
+        }
+    }
+
+    panic::set_hook(default_hook);
+
+    Ok(Report {
+        year,
+        input_description: match sample {
+            None => "the real inputs".to_string(),
+            Some("") => "the `{day}_sample` inputs".to_string(),
+            Some(sample) => format!("the `{{day}}_sample_{}` inputs", sample),
+        },
+        parts,
+        renders,
+    })
+}
+
+/// A bar of `width` characters, filled in proportion to `fraction`
+fn text_bar(fraction: f64, width: usize) -> String {
+    const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+    let eighths = (fraction * (width * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(EIGHTHS[eighths % 8]);
+    }
+    bar
+}
+
+fn markdown(report: &Report) -> String {
+    let mut out = String::new();
+    let slowest = report.slowest().as_secs_f64();
+
+    writeln!(out, "# Advent of Code {}", report.year).unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "All parts on {}, in {} in total.",
+        report.input_description,
+        format_duration(report.total()).trim()
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "| Day | Part | Answer | Status | Time | |").unwrap();
+    writeln!(out, "|----:|-----:|--------|--------|-----:|-|").unwrap();
+
+    let mut long_answers = Vec::new();
+    for part in &report.parts {
+        let answer = match &part.outcome {
+            Outcome::Answer(answer, _) if answer.trim().contains('\n') => {
+                long_answers.push((part.day, part.part, answer));
+                "*see below*".to_string()
+            }
+            Outcome::Answer(answer, _) => answer.replace('|', "\\|"),
+            _ => String::new(),
+        };
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | `{}` |",
+            part.day,
+            part.part,
+            answer,
+            status(&part.outcome),
+            format_duration(part.duration).trim(),
+            text_bar(part.duration.as_secs_f64() / slowest, 20)
+        )
+        .unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "## Slowest parts").unwrap();
+    writeln!(out).unwrap();
+    for (idx, part) in report.ranking().iter().enumerate() {
+        writeln!(
+            out,
+            "{}. Day {} part {}: {} ({:.0}% of the total)",
+            idx + 1,
+            part.day,
+            part.part,
+            format_duration(part.duration).trim(),
+            100. * part.duration.as_secs_f64() / report.total().as_secs_f64()
+        )
+        .unwrap();
+    }
+
+    for (day, part, answer) in long_answers {
+        writeln!(out).unwrap();
+        writeln!(out, "## Day {} part {}", day, part).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "```\n{}\n```", answer.trim_matches('\n')).unwrap();
+    }
+
+    for render in &report.renders {
+        writeln!(out).unwrap();
+        writeln!(out, "## Day {}", render.day).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "![Day {}]({})", render.day, render.data_uri).unwrap();
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn html(report: &Report) -> String {
+    let mut out = String::new();
+    let slowest = report.slowest().as_secs_f64();
+
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+    )
+    .unwrap();
+    writeln!(out, "<title>Advent of Code {}</title>", report.year).unwrap();
+    writeln!(
+        out,
+        "<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+td, th {{ padding: 0.2em 0.6em; border-bottom: 1px solid #ddd; text-align: left; }}
+td.time {{ text-align: right; font-family: monospace; }}
+pre {{ margin: 0; line-height: 1; }}
+.bar {{ background: #4a7; height: 1em; }}
+</style>"
+    )
+    .unwrap();
+    writeln!(out, "</head>\n<body>").unwrap();
+
+    writeln!(out, "<h1>Advent of Code {}</h1>", report.year).unwrap();
+    writeln!(
+        out,
+        "<p>All parts on {}, in {} in total.</p>",
+        escape_html(&report.input_description),
+        format_duration(report.total()).trim()
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        "<table>\n<tr><th>Day</th><th>Part</th><th>Answer</th><th>Status</th><th>Time</th><th></th></tr>"
+    )
+    .unwrap();
+    for part in &report.parts {
+        let answer = match &part.outcome {
+            Outcome::Answer(answer, _) => {
+                format!("<pre>{}</pre>", escape_html(answer.trim_matches('\n')))
+            }
+            _ => String::new(),
+        };
+        writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"time\">{}</td><td style=\"width: 200px\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td></tr>",
+            part.day,
+            part.part,
+            answer,
+            escape_html(&status(&part.outcome)),
+            format_duration(part.duration).trim(),
+            100. * part.duration.as_secs_f64() / slowest
+        )
+        .unwrap();
+    }
+    writeln!(out, "</table>").unwrap();
+
+    writeln!(out, "<h2>Slowest parts</h2>\n<ol>").unwrap();
+    for part in report.ranking() {
+        writeln!(
+            out,
+            "<li>Day {} part {}: {} ({:.0}% of the total)</li>",
+            part.day,
+            part.part,
+            format_duration(part.duration).trim(),
+            100. * part.duration.as_secs_f64() / report.total().as_secs_f64()
+        )
+        .unwrap();
+    }
+    writeln!(out, "</ol>").unwrap();
+
+    for render in &report.renders {
+        writeln!(
+            out,
+            "<h2>Day {}</h2>\n<img alt=\"Day {}\" src=\"{}\">",
+            render.day, render.day, render.data_uri
+        )
+        .unwrap();
+    }
+
+    writeln!(out, "</body>\n</html>").unwrap();
+    out
+}
+
+/// Run all of `year`'s solutions and write a report of the answers and timings to `output`
+/// Fails when it can't be written, or after writing it when a part was wrong or failed
+pub fn report(
+    config: &Config,
+    year: u16,
+    sample: Option<&str>,
+    format: Format,
+    output: &Path,
+) -> Result<(), String> {
+    let report = run(config, year, sample)?;
+    let text = match format {
+        Format::Markdown => markdown(&report),
+        Format::Html => html(&report),
+    };
+    fs::write(output, text).map_err(|e| format!("{}: {}", output.display(), e))?;
+
+    // The report is still worth reading when some parts failed, so it gets written anyway
+    let failed = report
+        .parts
+        .iter()
+        .filter(|p| {
+            matches!(
+                p.outcome,
+                Outcome::Answer(_, Verification::Wrong { .. })
+                    | Outcome::Failed(_)
+                    | Outcome::Panicked
+            )
+        })
+        .count();
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} parts gave a wrong answer or failed, see {}",
+            failed,
+            report.parts.len(),
+            output.display()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::{
+    panic,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    context::Context,
+    format_duration,
+    generate::generate,
+    registry::{Day, Implementation},
+};
+
+/// How many times each size is run, the fastest run is the one that counts
+const RUNS: usize = 3;
+
+/// Time the fastest of a few runs of `implementation`, or `None` if it panicked or failed
+fn time(implementation: &Implementation, input: &str) -> Option<Duration> {
+    let mut fastest: Option<Duration> = None;
+    for _ in 0..RUNS {
+        let before = Instant::now();
+        panic::catch_unwind(|| (implementation.solve)(input, &Context::default()))
+            .ok()?
+            .ok()?;
+        let duration = Instant::now() - before;
+
+        fastest = Some(fastest.map_or(duration, |f| f.min(duration)));
+
+        // Slow runs are not noisy enough to be worth repeating
+        if duration > Duration::from_millis(100) {
+            break;
+        }
+    }
+    fastest
+}
+
+/// Least squares fit of log(time) against log(size). The slope is the exponent k in O(n^k).
+fn growth_exponent(measurements: &[(usize, Duration)]) -> Option<f64> {
+    // Small inputs are dominated by constant overheads, so only look at the larger half
+    let points: Vec<(f64, f64)> = measurements[measurements.len() / 2..]
+        .iter()
+        .map(|(size, duration)| ((*size as f64).ln(), duration.as_secs_f64().ln()))
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    Some(covariance / variance)
+}
+
+pub struct Options {
+    /// The smallest size to run
+    pub start: usize,
+    /// How much the size grows each step
+    pub factor: f64,
+    /// The most sizes to run
+    pub steps: usize,
+    /// Stop growing once a single run takes longer than this
+    pub budget: Duration,
+    pub seed: u64,
+}
+
+fn scale_implementation(implementation: &Implementation, options: &Options) {
+    println!(
+        "[Day {:>2}][Part {}][{}]",
+        implementation.day, implementation.part, implementation.name
+    );
+    println!("{:>10}  {:>10}", "size", "time");
+
+    let mut measurements: Vec<(usize, Duration)> = Vec::new();
+    let mut size = options.start.max(1) as f64;
+    for _ in 0..options.steps {
+        let input = generate(
+            implementation.year,
+            implementation.day,
+            size as usize,
+            options.seed,
+        )
+        .unwrap();
+        match time(implementation, &input) {
+            Some(duration) => {
+                println!("{:>10}  {}", size as usize, format_duration(duration));
+                measurements.push((size as usize, duration));
+                if duration > options.budget {
+                    break;
+                }
+            }
+            None => {
+                println!("{:>10}  panicked", size as usize);
+                break;
+            }
+        }
+
+        // Make sure the size actually grows, even for small sizes and factors
+        size = (size * options.factor).max(size + 1.);
+    }
+
+    match growth_exponent(&measurements) {
+        Some(exponent) => println!("Estimated growth: O(n^{:.2})", exponent),
+        None => println!("Not enough measurements to estimate growth"),
+    }
+    println!();
+}
+
+/// Measure how the running time of `day`'s implementations grows with the input size. `size` is
+/// whatever the day's generator takes it to mean.
+pub fn scale(day: &Day, part: Option<u8>, options: &Options) {
+    if generate(day.year, day.day, 1, options.seed).is_none() {
+        println!("I have no input generator for day {}", day.day);
+        return;
+    }
+
+    // Panics are reported in the table
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    for implementation in day
+        .implementations
+        .iter()
+        .filter(|i| part.is_none_or(|p| i.part == p))
+    {
+        scale_implementation(implementation, options);
+    }
+
+    panic::set_hook(default_hook);
+}
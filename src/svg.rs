@@ -0,0 +1,49 @@
+use std::fmt::Write;
+
+/// A minimal SVG image made of filled rectangles
+pub struct Svg {
+    width: u32,
+    height: u32,
+    body: String,
+}
+
+impl Svg {
+    pub fn new(width: u32, height: u32, background: &str) -> Self {
+        let mut svg = Self {
+            width,
+            height,
+            body: String::new(),
+        };
+        svg.rect(0, 0, width, height, background);
+        svg
+    }
+
+    pub fn rect(&mut self, x: u32, y: u32, width: u32, height: u32, fill: &str) {
+        writeln!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x, y, width, height, fill
+        )
+        .unwrap();
+    }
+
+    /// The image as a `data:` URI, which can be embedded directly in HTML or Markdown
+    pub fn to_data_uri(&self) -> String {
+        format!(
+            "data:image/svg+xml;base64,{}",
+            base64::encode(self.to_string())
+        )
+    }
+}
+
+impl std::fmt::Display for Svg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" shape-rendering="crispEdges">"#,
+            self.width, self.height
+        )?;
+        write!(f, "{}", self.body)?;
+        writeln!(f, "</svg>")
+    }
+}
@@ -1,51 +1,233 @@
-pub fn neighbours<'a, T>(
-    map: &'a Vec<Vec<T>>,
-    row: usize,
-    col: usize,
-) -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
-    if let Some(_) = map.get(row).and_then(|r| r.get(col)) {
-        let north = if row > 0 { Some((row - 1, col)) } else { None };
-        let west = if col > 0 { Some((row, col - 1)) } else { None };
-        let south = if row < map.len() - 1 {
-            Some((row + 1, col))
-        } else {
-            None
-        };
-        let east = if col < map[0].len() - 1 {
-            Some((row, col + 1))
-        } else {
-            None
-        };
-
-        vec![north, west, south, east]
-    } else {
-        vec![]
-    }
-    .into_iter()
-    .flatten()
-    .map(|(nrow, ncol)| ((nrow, ncol), &map[nrow][ncol]))
-}
+use std::{
+    fmt::{self, Display},
+    ops::{Index, IndexMut},
+};
 
-pub fn neighbour_indices_with_diag(
-    row: usize,
-    col: usize,
-    height: usize,
+use crate::{
+    geometry::{Aabb, Point},
+    parse::{self, ParseError},
+};
+
+pub mod graph;
+
+/// A location in a grid as `(row, col)`
+pub type Position = (usize, usize);
+
+const OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
+const OFFSETS_WITH_DIAG: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangle of cells, stored row by row. Indexing with a `(row, col)` position panics when it
+/// is outside the grid, `get` and `set` don't.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
     width: usize,
-) -> Vec<(usize, usize)> {
-    [
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-        (0, -1),
-        (0, 1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-    ]
-    .iter()
-    .map(|(row_ofs, col_ofs)| ((row as isize + row_ofs), (col as isize + col_ofs)))
-    .filter(|(row, col)| 0 <= *row && 0 <= *col)
-    .map(|(row, col)| (row as usize, col as usize))
-    .filter(|(row, col)| *row < height && *col < width)
-    .collect()
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `value`
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// A grid from its rows, which must all be as long
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "All rows of a grid must be as long"
+        );
+
+        Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// A grid of single digits, one row per line
+    pub fn from_digits(text: &str) -> Result<Self, ParseError>
+    where
+        T: TryFrom<u8>,
+    {
+        parse::digit_grid(text).map(Self::from_rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cells as a box, with columns along `x` and rows along `y`. An empty grid is treated
+    /// as a single cell.
+    pub fn bounds(&self) -> Aabb {
+        Aabb {
+            min: Point::ORIGIN,
+            max: Point::new(self.width.max(1) as i64 - 1, self.height.max(1) as i64 - 1),
+        }
+    }
+
+    /// The number of cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    fn offset(&self, (row, col): Position) -> Option<usize> {
+        (row < self.height && col < self.width).then(|| row * self.width + col)
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.offset(position).is_some()
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.offset(position).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.offset(position).map(|offset| &mut self.cells[offset])
+    }
+
+    /// Replace the value at `position`, returning the old one. Returns `None` and leaves the grid
+    /// alone if `position` is outside the grid.
+    pub fn set(&mut self, position: Position, value: T) -> Option<T> {
+        self.get_mut(position)
+            .map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// All cells with their positions, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(offset, cell)| ((offset / width, offset % width), cell))
+    }
+
+    /// All cells with their positions, row by row
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Position, &mut T)> {
+        let width = self.width;
+        self.cells
+            .iter_mut()
+            .enumerate()
+            .map(move |(offset, cell)| ((offset / width, offset % width), cell))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `max(1)` as `chunks` doesn't allow 0, there are no cells anyway when the width is 0
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// A grid of the same size with `f` applied to every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    fn positions_at(
+        &self,
+        (row, col): Position,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Position> {
+        let (width, height) = (self.width, self.height);
+        offsets
+            .iter()
+            .map(move |(row_ofs, col_ofs)| (row as isize + row_ofs, col as isize + col_ofs))
+            .filter(move |(row, col)| {
+                (0..height as isize).contains(row) && (0..width as isize).contains(col)
+            })
+            .map(|(row, col)| (row as usize, col as usize))
+    }
+
+    /// The positions of the up to 4 cells sharing a side with `position`
+    pub fn neighbour_positions(&self, position: Position) -> impl Iterator<Item = Position> {
+        self.positions_at(position, &OFFSETS)
+    }
+
+    /// The positions of the up to 8 cells sharing a side or a corner with `position`
+    pub fn neighbour_positions_with_diag(
+        &self,
+        position: Position,
+    ) -> impl Iterator<Item = Position> {
+        self.positions_at(position, &OFFSETS_WITH_DIAG)
+    }
+
+    /// The up to 4 cells sharing a side with `position`, with their positions
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.neighbour_positions(position)
+            .map(move |position| (position, &self[position]))
+    }
+
+    /// The up to 8 cells sharing a side or a corner with `position`, with their positions
+    pub fn neighbours_with_diag(&self, position: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.neighbour_positions_with_diag(position)
+            .map(move |position| (position, &self[position]))
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        let (width, height) = (self.width, self.height);
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "Position {:?} is outside the {}x{} grid",
+                position, width, height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| {
+            panic!(
+                "Position {:?} is outside the {}x{} grid",
+                position, width, height
+            )
+        })
+    }
+}
+
+/// Every cell, one row per line
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
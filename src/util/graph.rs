@@ -0,0 +1,475 @@
+//! Searches over graphs given as a function from a node to its neighbours, so nothing has to be
+//! built up front. Nodes are usually small values like positions or names.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use super::{Grid, Position};
+
+/// What a depth-first search should do after visiting a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// Go on to the node's neighbours
+    Continue,
+    /// Don't go further down this path, but carry on with the others
+    Prune,
+    /// End the whole search
+    Stop,
+}
+
+/// Decides which nodes a depth-first search may enter, given the path that leads to them.
+/// Closures taking the path and the node are policies too.
+pub trait VisitPolicy<N> {
+    /// Whether the search may go from the end of `path` to `node`, which it does straight away
+    /// if so
+    fn may_visit(&mut self, path: &[N], node: &N) -> bool;
+
+    /// Called when the search leaves `node` again, going back to the end of `path`. Policies
+    /// that keep count of what is on the path can undo entering it here, rather than look
+    /// through the whole path for every node.
+    fn leave(&mut self, _path: &[N], _node: &N) {}
+}
+
+impl<N, F: FnMut(&[N], &N) -> bool> VisitPolicy<N> for F {
+    fn may_visit(&mut self, path: &[N], node: &N) -> bool {
+        self(path, node)
+    }
+}
+
+/// Enter every node at most once in the whole search, the classic depth-first search
+pub struct Once<N>(HashSet<N>);
+
+impl<N> Default for Once<N> {
+    fn default() -> Self {
+        Self(HashSet::new())
+    }
+}
+
+impl<N: Eq + Hash + Clone> VisitPolicy<N> for Once<N> {
+    fn may_visit(&mut self, _path: &[N], node: &N) -> bool {
+        self.0.insert(node.clone())
+    }
+}
+
+/// Enter every node at most once per path, which goes down every simple path
+pub struct OncePerPath;
+
+impl<N: PartialEq> VisitPolicy<N> for OncePerPath {
+    fn may_visit(&mut self, path: &[N], node: &N) -> bool {
+        !path.contains(node)
+    }
+}
+
+/// Depth-first search from `start`. `visit` is called with the path to every node that `policy`
+/// lets the search enter, the node itself being the last one on the path.
+pub fn dfs<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    policy: &mut impl VisitPolicy<N>,
+    mut visit: impl FnMut(&[N]) -> Visit,
+) where
+    N: Clone,
+    I: IntoIterator<Item = N>,
+{
+    fn go<N: Clone, I: IntoIterator<Item = N>>(
+        path: &mut Vec<N>,
+        neighbours: &mut impl FnMut(&N) -> I,
+        policy: &mut impl VisitPolicy<N>,
+        visit: &mut impl FnMut(&[N]) -> Visit,
+    ) -> Visit {
+        match visit(path) {
+            Visit::Continue => (),
+            other => return other,
+        }
+
+        let node = path.last().unwrap().clone();
+        for next in neighbours(&node) {
+            if policy.may_visit(path, &next) {
+                path.push(next);
+                let result = go(path, neighbours, policy, visit);
+                let next = path.pop().unwrap();
+                policy.leave(path, &next);
+                if result == Visit::Stop {
+                    return Visit::Stop;
+                }
+            }
+        }
+        Visit::Continue
+    }
+
+    if policy.may_visit(&[], &start) {
+        let mut path = vec![start];
+        go(&mut path, &mut neighbours, policy, &mut visit);
+        policy.leave(&[], &path[0]);
+    }
+}
+
+/// The path from the start of a search to `goal`, following the `parents` it recorded
+fn reconstruct<N: Eq + Hash + Clone>(parents: &HashMap<N, N>, goal: N) -> Vec<N> {
+    let mut path = vec![goal];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// The distance in steps from `start` to every node that can be reached from it
+pub fn bfs_distances<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut frontier = VecDeque::from([start]);
+    while let Some(node) = frontier.pop_front() {
+        let distance = distances[&node];
+        for next in neighbours(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                frontier.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// The shortest path in steps from `start` to a node for which `is_goal` holds, including both
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::new();
+    let mut seen = HashSet::from([start.clone()]);
+    let mut frontier = VecDeque::from([start]);
+    while let Some(node) = frontier.pop_front() {
+        if is_goal(&node) {
+            return Some(reconstruct(&parents, node));
+        }
+        for next in neighbours(&node) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                frontier.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// The cheapest path from `start` to a node for which `is_goal` holds, with its cost.
+/// `neighbours` gives each neighbour with the cost of moving to it.
+pub fn dijkstra<N, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<(u64, Vec<N>)>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(start, neighbours, |_| 0, is_goal)
+}
+
+/// Like `dijkstra`, but guided by `heuristic`, an estimate of the cost from a node to the goal.
+/// The path is only guaranteed to be the cheapest if the heuristic never overestimates.
+pub fn astar<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> u64,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(u64, Vec<N>)>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut costs = HashMap::from([(start.clone(), 0)]);
+    let mut parents = HashMap::new();
+    // Nodes aren't required to be `Ord`, so the queue refers to them by their index in `nodes`
+    let mut nodes = vec![start.clone()];
+    let mut queue = BinaryHeap::from([Reverse((heuristic(&start), 0, 0))]);
+
+    while let Some(Reverse((_, cost, idx))) = queue.pop() {
+        let node = nodes[idx].clone();
+        if cost > costs[&node] {
+            // A cheaper way to this node was found after this one was queued
+            continue;
+        }
+        if is_goal(&node) {
+            return Some((cost, reconstruct(&parents, node)));
+        }
+
+        for (next, step_cost) in neighbours(&node) {
+            let next_cost = cost + step_cost;
+            if costs.get(&next).is_none_or(|known| next_cost < *known) {
+                costs.insert(next.clone(), next_cost);
+                parents.insert(next.clone(), node.clone());
+                queue.push(Reverse((
+                    next_cost + heuristic(&next),
+                    next_cost,
+                    nodes.len(),
+                )));
+                nodes.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Neighbours for searching a grid, moving to the up to 4 cells next to a position when
+/// `passable(from, to)` holds for their values
+pub fn grid_neighbours<'a, T>(
+    grid: &'a Grid<T>,
+    passable: impl Fn(&T, &T) -> bool + 'a,
+) -> impl Fn(&Position) -> Vec<Position> + 'a {
+    move |&position| {
+        grid.neighbours(position)
+            .filter(|(_, value)| passable(&grid[position], value))
+            .map(|(next, _)| next)
+            .collect()
+    }
+}
+
+/// Weighted neighbours for searching a grid, where entering a cell costs `cost` of its value.
+/// Cells with no cost can't be entered.
+pub fn grid_weighted_neighbours<'a, T>(
+    grid: &'a Grid<T>,
+    cost: impl Fn(&T) -> Option<u64> + 'a,
+) -> impl Fn(&Position) -> Vec<(Position, u64)> + 'a {
+    move |&position| {
+        grid.neighbours(position)
+            .filter_map(|(next, value)| Some((next, cost(value)?)))
+            .collect()
+    }
+}
+
+/// A heuristic for `astar` on a grid, which never overestimates when every step costs at least 1
+pub fn manhattan_to(goal: Position) -> impl Fn(&Position) -> u64 {
+    move |&(row, col)| (row.abs_diff(goal.0) + col.abs_diff(goal.1)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entering a cell costs its digit, 0 is a wall. The cheapest way from the top left to the
+    /// bottom right goes down and along the bottom for 8, the top way is as short but costs 12.
+    const GRID: &str = "1163\n1081\n2131";
+    const START: Position = (0, 0);
+    const GOAL: Position = (2, 3);
+
+    fn grid() -> Grid<u8> {
+        Grid::from_digits(GRID).unwrap()
+    }
+
+    fn cost(value: &u8) -> Option<u64> {
+        (*value != 0).then_some(*value as u64)
+    }
+
+    fn open(_from: &u8, to: &u8) -> bool {
+        *to != 0
+    }
+
+    /// A small directed graph with two ways from `a` to `d` and a way back from `c` to `a`
+    fn graph(node: &char) -> Vec<char> {
+        match node {
+            'a' => vec!['b', 'c'],
+            'b' => vec!['d'],
+            'c' => vec!['d', 'a'],
+            'd' => vec!['e'],
+            _ => vec![],
+        }
+    }
+
+    /// The paths `dfs` visits from `a`, as strings, with `visit` deciding how to go on
+    fn visited(
+        policy: &mut impl VisitPolicy<char>,
+        mut visit: impl FnMut(&[char]) -> Visit,
+    ) -> Vec<String> {
+        let mut paths = Vec::new();
+        dfs('a', graph, policy, |path| {
+            paths.push(path.iter().collect());
+            visit(path)
+        });
+        paths
+    }
+
+    #[test]
+    fn dfs_once_enters_every_node_once() {
+        assert_eq!(
+            visited(&mut Once::default(), |_| Visit::Continue),
+            ["a", "ab", "abd", "abde", "ac"]
+        );
+    }
+
+    #[test]
+    fn dfs_once_per_path_goes_down_every_simple_path() {
+        assert_eq!(
+            visited(&mut OncePerPath, |_| Visit::Continue),
+            ["a", "ab", "abd", "abde", "ac", "acd", "acde"]
+        );
+    }
+
+    #[test]
+    fn dfs_takes_closures_as_policies() {
+        // Only paths of up to 3 nodes, but the way back to `a` is allowed
+        let mut short = |path: &[char], _: &char| path.len() < 3;
+        assert_eq!(
+            visited(&mut short, |_| Visit::Continue),
+            ["a", "ab", "abd", "ac", "acd", "aca"]
+        );
+    }
+
+    #[test]
+    fn dfs_prune_skips_what_is_past_a_node() {
+        let prune_at_d = |path: &[char]| {
+            if path.last() == Some(&'d') {
+                Visit::Prune
+            } else {
+                Visit::Continue
+            }
+        };
+        assert_eq!(
+            visited(&mut OncePerPath, prune_at_d),
+            ["a", "ab", "abd", "ac", "acd"]
+        );
+    }
+
+    #[test]
+    fn dfs_stop_ends_the_search() {
+        let stop_at_d = |path: &[char]| {
+            if path.last() == Some(&'d') {
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        };
+        assert_eq!(visited(&mut OncePerPath, stop_at_d), ["a", "ab", "abd"]);
+    }
+
+    /// Keeps the path itself from what it is told, to check that entering and leaving match up
+    #[derive(Default)]
+    struct Follow(Vec<char>);
+
+    impl VisitPolicy<char> for Follow {
+        fn may_visit(&mut self, path: &[char], node: &char) -> bool {
+            assert_eq!(self.0, path);
+            let enter = !path.contains(node);
+            if enter {
+                self.0.push(*node);
+            }
+            enter
+        }
+
+        fn leave(&mut self, path: &[char], node: &char) {
+            assert_eq!(self.0.pop(), Some(*node));
+            assert_eq!(self.0, path);
+        }
+    }
+
+    #[test]
+    fn dfs_leaves_every_node_it_enters() {
+        let mut follow = Follow::default();
+        visited(&mut follow, |_| Visit::Continue);
+        assert!(follow.0.is_empty());
+
+        let mut follow = Follow::default();
+        let stop_at_e = |path: &[char]| {
+            if path.last() == Some(&'e') {
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        };
+        assert_eq!(visited(&mut follow, stop_at_e).last().unwrap(), "abde");
+        assert!(follow.0.is_empty());
+    }
+
+    #[test]
+    fn grid_neighbours_skip_walls_and_edges() {
+        let grid = grid();
+        assert_eq!(grid_neighbours(&grid, open)(&(0, 1)), [(0, 0), (0, 2)]);
+        assert_eq!(
+            grid_weighted_neighbours(&grid, cost)(&(1, 0)),
+            [((0, 0), 1), ((2, 0), 2)]
+        );
+    }
+
+    #[test]
+    fn bfs_finds_a_shortest_path() {
+        let grid = grid();
+        let path = bfs(START, grid_neighbours(&grid, open), |&p| p == GOAL).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!((path[0], path[5]), (START, GOAL));
+        assert!(path.windows(2).all(|step| {
+            let ((r1, c1), (r2, c2)) = (step[0], step[1]);
+            r1.abs_diff(r2) + c1.abs_diff(c2) == 1 && grid[step[1]] != 0
+        }));
+
+        assert_eq!(
+            bfs(START, grid_neighbours(&grid, open), |&p| p == (1, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn bfs_distances_reach_everything_but_walls() {
+        let grid = grid();
+        let distances = bfs_distances(START, grid_neighbours(&grid, open));
+        assert_eq!(distances.len(), 11);
+        assert_eq!(distances[&START], 0);
+        assert_eq!(distances[&(1, 2)], 3);
+        assert_eq!(distances[&GOAL], 5);
+        assert!(!distances.contains_key(&(1, 1)));
+    }
+
+    #[test]
+    fn dijkstra_finds_the_cheapest_path() {
+        let grid = grid();
+        let cheapest = vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (2, 3)];
+        assert_eq!(
+            dijkstra(START, grid_weighted_neighbours(&grid, cost), |&p| p == GOAL),
+            Some((8, cheapest))
+        );
+        assert_eq!(
+            dijkstra(START, grid_weighted_neighbours(&grid, cost), |&p| p
+                == (0, 3)),
+            Some((10, vec![(0, 0), (0, 1), (0, 2), (0, 3)]))
+        );
+        assert_eq!(
+            dijkstra(START, grid_weighted_neighbours(&grid, cost), |&p| p
+                == (1, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn astar_agrees_with_dijkstra() {
+        let grid = grid();
+        assert_eq!(manhattan_to(GOAL)(&START), 5);
+        assert_eq!(manhattan_to(GOAL)(&GOAL), 0);
+
+        // Ties may be broken either way, but the costs are the same
+        let cost_to = |goal: Position, heuristic: &dyn Fn(&Position) -> u64| {
+            astar(
+                START,
+                grid_weighted_neighbours(&grid, cost),
+                heuristic,
+                |&p| p == goal,
+            )
+            .map(|(cost, _)| cost)
+        };
+        for (goal, _) in grid.iter() {
+            assert_eq!(cost_to(goal, &manhattan_to(goal)), cost_to(goal, &|_| 0));
+        }
+        assert_eq!(cost_to(GOAL, &manhattan_to(GOAL)), Some(8));
+        assert_eq!(cost_to((1, 1), &manhattan_to((1, 1))), None);
+    }
+}
@@ -1,12 +1,30 @@
 use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
-use sdl2::render::{Canvas, TextureQuery};
-use sdl2::ttf::Font;
-use sdl2::video::Window;
-use sdl2::{event::Event, render::WindowCanvas};
+use sdl2::render::{Canvas, RenderTarget, TextureQuery};
+use sdl2::surface::Surface;
+use sdl2::video::{FullscreenType, Window};
+use sdl2::{
+    event::{Event, WindowEvent},
+    render::WindowCanvas,
+};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+use camera::Camera;
+use export::{FrameRange, Recorder, EXPORT_FPS};
+use font::TextFont;
+
+use crate::geometry::Aabb;
+
+pub mod camera;
+pub mod export;
+pub mod font;
+pub mod simulation;
+pub mod term;
+
 pub enum TextBackground {
     Solid(Color),
     Transparent,
@@ -14,12 +32,28 @@ pub enum TextBackground {
 
 pub fn show_text(
     canvas: &mut WindowCanvas,
-    font: &Font,
+    font: &TextFont,
     background: TextBackground,
     x: i32,
     y: i32,
     text: &str,
 ) -> Result<Rect, String> {
+    let font = match font {
+        TextFont::Ttf(font) => font,
+        TextFont::Bitmap { scale } => {
+            let (width, height) = font::bitmap_text_size(text, *scale);
+            let target_rect = Rect::new(x, y, width, height);
+            let draw_color = canvas.draw_color();
+            if let TextBackground::Solid(bg) = background {
+                canvas.set_draw_color(bg);
+                canvas.fill_rect(target_rect)?;
+            }
+            font::draw_bitmap_text(canvas, *scale, x, y, text, Color::WHITE)?;
+            canvas.set_draw_color(draw_color);
+            return Ok(target_rect);
+        }
+    };
+
     let texture_creator = canvas.texture_creator();
     let surface = match background {
         TextBackground::Solid(bg) => font.render(text).shaded(Color::WHITE, bg),
@@ -35,40 +69,409 @@ pub fn show_text(
     Ok(target_rect)
 }
 
+/// Where the frames of a `WindowApp` go, as given on the command line
+#[derive(Debug, Default, Clone)]
+pub struct Output {
+    /// Render this many frames offscreen instead of opening a window
+    pub headless: Option<usize>,
+    /// Show the frames in the terminal instead of in a window
+    pub term: bool,
+    /// A directory to write frames to as numbered PNG files
+    pub export_frames: Option<PathBuf>,
+    /// A file to write the frames to as an animated GIF
+    pub gif: Option<PathBuf>,
+    /// How long each frame of the GIF shows, by default as long as it would in the window
+    pub gif_delay: Option<Duration>,
+    /// A file to write the frames to as a YUV4MPEG2 video, `-` for stdout
+    pub y4m: Option<PathBuf>,
+    /// Which frames to export
+    pub frames: FrameRange,
+    /// Make exported frames this many times smaller, 0 and 1 leave them as they are
+    pub downscale: u32,
+}
+
+/// Settings for `run_window` that come from the configuration rather than from the app
+pub struct WindowSettings {
+    /// The TTF font for text, another one is looked for if it doesn't exist
+    pub font: PathBuf,
+    /// Overrides the app's `WINDOW_FPS`, 0 means as fast as possible
+    pub fps: Option<u32>,
+    pub output: Output,
+}
+
+/// The pixels of a presented frame, 3 bytes of RGB per pixel, row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Read back what has been drawn on `canvas`
+    pub fn read<T: RenderTarget>(canvas: &Canvas<T>) -> Result<Self, String> {
+        let (width, height) = canvas.output_size()?;
+        Ok(Self {
+            width,
+            height,
+            pixels: canvas.read_pixels(None, PixelFormatEnum::RGB24)?,
+        })
+    }
+
+    /// The frame `factor` times smaller in both directions, each pixel the average of the block
+    /// it replaces. Pixels at the right and bottom edges that don't fill a block are dropped.
+    pub fn downscale(&self, factor: u32) -> Frame {
+        if factor <= 1 {
+            return self.clone();
+        }
+
+        let (width, height) = (self.width / factor, self.height / factor);
+        let mut pixels = Vec::with_capacity(3 * (width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0u32; 3];
+                for dy in 0..factor {
+                    for dx in 0..factor {
+                        let offset =
+                            3 * ((y * factor + dy) * self.width + x * factor + dx) as usize;
+                        for (channel, total) in sum.iter_mut().enumerate() {
+                            *total += self.pixels[offset + channel] as u32;
+                        }
+                    }
+                }
+                pixels.extend(sum.map(|total| (total / (factor * factor)) as u8));
+            }
+        }
+
+        Frame {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The `width` by `height` pixels around the centre of the frame, black where the frame is
+    /// smaller than that
+    pub fn centred(&self, width: u32, height: u32) -> Frame {
+        if (width, height) == (self.width, self.height) {
+            return self.clone();
+        }
+
+        let left = (self.width as i64 - width as i64).div_euclid(2);
+        let top = (self.height as i64 - height as i64).div_euclid(2);
+        let mut pixels = vec![0; 3 * (width * height) as usize];
+        for y in 0..height {
+            let from_y = top + y as i64;
+            if !(0..self.height as i64).contains(&from_y) {
+                continue;
+            }
+            for x in 0..width {
+                let from_x = left + x as i64;
+                if (0..self.width as i64).contains(&from_x) {
+                    let from = 3 * (from_y as usize * self.width as usize + from_x as usize);
+                    let to = 3 * (y * width + x) as usize;
+                    pixels[to..to + 3].copy_from_slice(&self.pixels[from..from + 3]);
+                }
+            }
+        }
+
+        Frame {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let offset = 3 * (y * self.width + x) as usize;
+        Color::RGB(
+            self.pixels[offset],
+            self.pixels[offset + 1],
+            self.pixels[offset + 2],
+        )
+    }
+}
+
+/// Draws a `WindowApp` on an offscreen software canvas rather than in a window, so it can run
+/// on a machine without a display, e.g. to export it or to test it
+pub struct Headless {
+    canvas: Canvas<Surface<'static>>,
+}
+
+impl Headless {
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        let surface = Surface::new(width, height, PixelFormatEnum::RGB24)?;
+        Ok(Self {
+            canvas: surface.into_canvas()?,
+        })
+    }
+
+    /// A canvas the size of `A`'s window
+    pub fn for_app<A: WindowApp>() -> Result<Self, String> {
+        Self::new(A::WINDOW_WIDTH, A::WINDOW_HEIGHT)
+    }
+
+    /// Draw up to `frames` frames of `app`, `frame_time` apart, stopping early when it has
+    /// nothing new to draw. `on_frame` gets every frame that was drawn. Returns how many there
+    /// were.
+    pub fn run<A: WindowApp>(
+        &mut self,
+        app: &mut A,
+        frames: usize,
+        frame_time: Duration,
+        mut on_frame: impl FnMut(&Frame) -> Result<(), String>,
+    ) -> Result<usize, String> {
+        let (width, height) = self.canvas.output_size()?;
+        let camera = initial_camera(app, width, height, 1.);
+        for drawn in 0..frames {
+            // The first frame shows where the app starts
+            if drawn > 0 {
+                app.update(frame_time);
+            }
+            if !app.draw_frame(&mut self.canvas, &camera)? {
+                return Ok(drawn);
+            }
+            on_frame(&self.frame()?)?;
+        }
+        Ok(frames)
+    }
+
+    /// What is on the canvas now
+    pub fn frame(&self) -> Result<Frame, String> {
+        Frame::read(&self.canvas)
+    }
+}
+
+/// A camera for a `width` by `height` pixel screen, zoomed like `app` wants to start
+fn initial_camera<A: WindowApp>(app: &A, width: u32, height: u32, scale: f64) -> Camera {
+    let mut camera = Camera::new(width, height, scale);
+    match (app.zoom(), app.content()) {
+        (Some(zoom), _) => camera.set_zoom(zoom),
+        (None, Some(content)) => camera.fit(content),
+        (None, None) => (),
+    }
+    camera
+}
+
+/// How many frames back `run_window` can rewind
+const HISTORY_LENGTH: usize = 1000;
+
+/// How long `run_window` waits when there is nothing to draw, e.g. while paused
+const IDLE_SLEEP: Duration = Duration::from_millis(10);
+
+/// Pausing, stepping and rewinding an app, the same in a window and in the terminal
+struct Playback {
+    paused: bool,
+    /// Take a single step while paused
+    step: bool,
+    /// Draw the app again while paused, e.g. after the camera moved
+    redraw: bool,
+    /// The snapshots from before the app last changed, the newest at the back
+    history: VecDeque<Box<dyn Any>>,
+    /// When the app was last moved on, or would have been if it wasn't paused
+    last_update: Instant,
+}
+
+impl Playback {
+    fn new() -> Self {
+        Self {
+            paused: false,
+            step: false,
+            redraw: false,
+            history: VecDeque::new(),
+            last_update: Instant::now(),
+        }
+    }
+
+    fn reset<A: WindowApp>(&mut self, app: &mut A) {
+        app.reset();
+        self.history.clear();
+        self.redraw = true;
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Pause and take just the next step
+    fn step(&mut self) {
+        self.paused = true;
+        self.step = true;
+    }
+
+    /// Pause and go back to before the last change
+    fn rewind<A: WindowApp>(&mut self, app: &mut A) {
+        self.paused = true;
+        if let Some(snapshot) = self.history.pop_back() {
+            app.restore(snapshot.as_ref());
+            self.redraw = true;
+        }
+    }
+
+    /// Draw the app again even if paused
+    fn redraw(&mut self) {
+        self.redraw = true;
+    }
+
+    /// Whether nothing gets drawn until the user says so
+    fn waiting(&self) -> bool {
+        self.paused && !self.step && !self.redraw
+    }
+
+    /// Move `app` on by the time since the last frame, or by a step, and say whether to draw it.
+    /// The state from before a change is saved to rewind to later.
+    fn next_frame<A: WindowApp>(&mut self, app: &mut A) -> bool {
+        let now = Instant::now();
+        let elapsed = now - self.last_update;
+        self.last_update = now;
+        if self.waiting() {
+            return false;
+        }
+
+        let snapshot = app.snapshot();
+        let changed = if self.step {
+            app.step()
+        } else if !self.paused {
+            app.update(elapsed)
+        } else {
+            false
+        };
+        if let Some(snapshot) = snapshot.filter(|_| changed) {
+            if self.history.len() == HISTORY_LENGTH {
+                self.history.pop_front();
+            }
+            self.history.push_back(snapshot);
+        }
+
+        self.step = false;
+        self.redraw = false;
+        true
+    }
+}
+
+/// The frame rate one slower, or 60 when there is no limit
+fn slower(fps: Option<u32>) -> Option<u32> {
+    fps.map(|f| if f > 1 { f - 1 } else { f }).or(Some(60))
+}
+
+/// The frame rate one faster, or 60 when there is no limit
+fn faster(fps: Option<u32>) -> Option<u32> {
+    fps.map(|f| f.saturating_add(1)).or(Some(60))
+}
+
 pub trait WindowApp {
     const WINDOW_NAME: &'static str;
+    /// The size the window opens at. It can be resized, so only use this to pick a size.
     const WINDOW_WIDTH: u32;
     const WINDOW_HEIGHT: u32;
     const WINDOW_FPS: Option<u32>;
     const SHOW_FPS: bool = true;
 
-    fn draw_frame(&mut self, canvas: &mut Canvas<Window>) -> Result<bool, String>;
+    /// Draw the app as it is now through `camera`, returns whether there is anything new to show.
+    /// The canvas is `camera.screen_size()` pixels big, which changes when the window is resized.
+    fn draw_frame<T: RenderTarget>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        camera: &Camera,
+    ) -> Result<bool, String>;
     fn handle_event(&mut self, _event: Event) {}
     fn reset(&mut self) {}
 
-    fn run_window(&mut self) {
+    /// Move on by `elapsed` of real time, returns whether anything changed. Drawing doesn't move
+    /// an app on, so how fast it goes doesn't depend on the frame rate.
+    fn update(&mut self, _elapsed: Duration) -> bool {
+        false
+    }
+
+    /// Move on by the smallest step there is, for stepping through while paused. Returns whether
+    /// anything changed.
+    fn step(&mut self) -> bool {
+        false
+    }
+
+    /// The part of the world the app draws in, for fitting it to the screen
+    fn content(&self) -> Option<Aabb> {
+        None
+    }
+
+    /// Pixels per world unit to start with. By default the content is fitted to the screen, or
+    /// world units are pixels if there is no content.
+    fn zoom(&self) -> Option<f64> {
+        None
+    }
+
+    /// A copy of the state `draw_frame` is about to draw from, for rewinding to it later. Apps
+    /// that don't give one can't be rewound.
+    fn snapshot(&self) -> Option<Box<dyn Any>> {
+        None
+    }
+
+    /// Go back to a state from `snapshot`
+    fn restore(&mut self, _snapshot: &dyn Any) {}
+
+    fn run_window(&mut self, settings: &WindowSettings)
+    where
+        Self: Sized,
+    {
+        let mut target_fps = match settings.fps {
+            Some(0) => None,
+            Some(fps) => Some(fps),
+            None => Self::WINDOW_FPS,
+        };
+        let mut recorder = Recorder::new(&settings.output, target_fps).unwrap();
+
+        if let Some(frames) = settings.output.headless {
+            // As long as each frame shows for in an export
+            let frame_time = Duration::from_secs(1) / target_fps.unwrap_or(EXPORT_FPS).max(1);
+            let drawn = Headless::for_app::<Self>()
+                .and_then(|mut headless| {
+                    headless.run(self, frames, frame_time, |frame| recorder.record(frame))
+                })
+                .unwrap();
+            // Not on stdout, which may be taken by a video
+            eprintln!("Rendered {} frames offscreen", drawn);
+            finish_recording(recorder);
+            return;
+        }
+
+        if settings.output.term {
+            term::run(self, target_fps, &mut recorder).unwrap();
+            finish_recording(recorder);
+            return;
+        }
+
         let sdl_context = sdl2::init().unwrap();
         let video_subsystem = sdl_context.video().unwrap();
-        let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string()).unwrap();
+        let ttf_context = sdl2::ttf::init().ok();
         let window = video_subsystem
             .window(Self::WINDOW_NAME, Self::WINDOW_WIDTH, Self::WINDOW_HEIGHT)
             .position_centered()
+            .resizable()
+            .allow_highdpi()
             .build()
             .unwrap();
         let mut canvas: Canvas<Window> = window.into_canvas().build().unwrap();
 
-        // Load a font
-        let mut font = ttf_context
-            .load_font("/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf", 18)
-            .unwrap();
-        font.set_style(sdl2::ttf::FontStyle::BOLD);
+        let (width, height) = canvas.output_size().unwrap();
+        let mut camera = initial_camera(self, width, height, window_scale(&canvas).unwrap());
+
+        // Text as big on HiDPI screens, as far as the screen the window opens on goes
+        let font = TextFont::load(
+            ttf_context.as_ref(),
+            &settings.font,
+            (18. * camera.scale()).round() as u16,
+        );
 
-        let mut target_fps = Self::WINDOW_FPS;
         let mut event_pump = sdl_context.event_pump().unwrap();
         let mut frame_time_counter = Instant::now();
 
+        let mut playback = Playback::new();
+
         'running: loop {
-            for event in event_pump.poll_iter() {
+            // Collected first, as the camera needs the mouse state from the event pump
+            let events: Vec<Event> = event_pump.poll_iter().collect();
+            let mut camera_moved = false;
+            for event in events {
                 match event {
                     Event::Quit { .. }
                     | Event::KeyDown {
@@ -78,24 +481,70 @@ pub trait WindowApp {
                     Event::KeyDown {
                         keycode: Some(Keycode::R),
                         ..
-                    } => self.reset(),
+                    } => playback.reset(self),
                     Event::KeyDown {
-                        keycode: Some(Keycode::O),
+                        keycode: Some(Keycode::Space),
+                        ..
+                    } => playback.toggle_pause(),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Right),
+                        ..
+                    } => playback.step(),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Left),
+                        ..
+                    } => playback.rewind(self),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::F11),
                         ..
                     } => {
-                        target_fps = target_fps
-                            .map(|f| if f > 1 { f - 1 } else { f })
-                            .or(Some(60))
+                        let window = canvas.window_mut();
+                        let fullscreen = match window.fullscreen_state() {
+                            FullscreenType::Off => FullscreenType::Desktop,
+                            _ => FullscreenType::Off,
+                        };
+                        window.set_fullscreen(fullscreen).unwrap();
                     }
+                    Event::Window {
+                        win_event: WindowEvent::SizeChanged(..),
+                        ..
+                    } => {
+                        let (width, height) = canvas.output_size().unwrap();
+                        camera.resize(width, height, window_scale(&canvas).unwrap());
+                        camera_moved = true;
+                    }
+                    Event::KeyDown {
+                        keycode: Some(Keycode::O),
+                        ..
+                    } => target_fps = slower(target_fps),
                     Event::KeyDown {
                         keycode: Some(Keycode::P),
                         ..
-                    } => target_fps = target_fps.map(|f| f.saturating_add(1)).or(Some(60)),
-                    _ => self.handle_event(event),
+                    } => target_fps = faster(target_fps),
+                    _ => {
+                        if camera.handle_event(&event, event_pump.mouse_state(), self.content()) {
+                            camera_moved = true;
+                        } else {
+                            self.handle_event(event);
+                        }
+                    }
                 }
             }
 
-            if self.draw_frame(&mut canvas).unwrap() {
+            // This also fills a window that got bigger
+            if camera_moved {
+                playback.redraw();
+            }
+
+            if !playback.next_frame(self) {
+                std::thread::sleep(IDLE_SLEEP);
+                continue;
+            }
+
+            if self.draw_frame(&mut canvas, &camera).unwrap() {
+                // Before the frame rate is drawn over it
+                recorder.record_canvas(&canvas).unwrap();
+
                 if let Some(fps) = target_fps {
                     std::thread::sleep(Duration::new(0, 1_000_000_000u32 / fps));
                 }
@@ -113,7 +562,83 @@ pub trait WindowApp {
                 )
                 .unwrap();
                 canvas.present();
+            } else {
+                // Nothing new, e.g. a finished app, so wait for the next frame without busy looping
+                std::thread::sleep(
+                    target_fps.map_or(IDLE_SLEEP, |fps| Duration::from_secs(1) / fps.max(1)),
+                );
             }
         }
+
+        finish_recording(recorder);
+    }
+}
+
+/// Pixels per window coordinate, which is more than 1 on HiDPI screens
+fn window_scale(canvas: &WindowCanvas) -> Result<f64, String> {
+    let (pixels, _) = canvas.output_size()?;
+    let (points, _) = canvas.window().size();
+    Ok(if points == 0 {
+        1.
+    } else {
+        pixels as f64 / points as f64
+    })
+}
+
+fn finish_recording(recorder: Recorder) {
+    if recorder.is_recording() {
+        eprintln!("Exported {} frames", recorder.finish().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame where the red of each pixel is `10 * x + y`
+    fn gradient(width: u32, height: u32) -> Frame {
+        Frame {
+            width,
+            height,
+            pixels: (0..height)
+                .flat_map(|y| (0..width).flat_map(move |x| [(10 * x + y) as u8, 0, 255]))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn downscale_averages_blocks() {
+        let frame = gradient(5, 3).downscale(2);
+        // The last column and row don't fill a block
+        assert_eq!((frame.width, frame.height), (2, 1));
+        assert_eq!(frame.pixels.len(), 2 * 3);
+        // (0 + 10 + 1 + 11) / 4 and (20 + 30 + 21 + 31) / 4, rounded down
+        assert_eq!(frame.pixel(0, 0), Color::RGB(5, 0, 255));
+        assert_eq!(frame.pixel(1, 0), Color::RGB(25, 0, 255));
+    }
+
+    #[test]
+    fn downscale_by_one_keeps_the_frame() {
+        assert_eq!(gradient(5, 3).downscale(1), gradient(5, 3));
+        assert_eq!(gradient(5, 3).downscale(0), gradient(5, 3));
+    }
+
+    #[test]
+    fn centred_crops_and_pads_around_the_centre() {
+        let frame = gradient(5, 3).centred(3, 1);
+        assert_eq!((frame.width, frame.height), (3, 1));
+        assert_eq!(frame.pixel(0, 0), Color::RGB(11, 0, 255));
+        assert_eq!(frame.pixel(2, 0), Color::RGB(31, 0, 255));
+
+        let frame = gradient(2, 2).centred(4, 3);
+        assert_eq!((frame.width, frame.height), (4, 3));
+        // The odd row of padding goes on top
+        assert_eq!(frame.pixel(1, 0), Color::RGB(0, 0, 0));
+        assert_eq!(frame.pixel(0, 1), Color::RGB(0, 0, 0));
+        assert_eq!(frame.pixel(1, 1), Color::RGB(0, 0, 255));
+        assert_eq!(frame.pixel(2, 2), Color::RGB(11, 0, 255));
+        assert_eq!(frame.pixel(3, 2), Color::RGB(0, 0, 0));
+
+        assert_eq!(gradient(5, 3).centred(5, 3), gradient(5, 3));
     }
 }
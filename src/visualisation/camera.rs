@@ -0,0 +1,176 @@
+//! Where the world an app draws is on screen, so apps can draw in their own units, e.g. grid
+//! cells, and the user can zoom and pan around
+
+use sdl2::{event::Event, keyboard::Keycode, mouse::MouseState, rect::Rect};
+
+use crate::geometry::Aabb;
+
+/// How much one notch of the mouse wheel zooms
+const WHEEL_ZOOM: f64 = 1.1;
+
+/// Maps world coordinates to pixels: screen = (world - offset) * zoom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// Pixels per world unit
+    zoom: f64,
+    /// The world coordinates at the top left corner of the screen
+    offset: (f64, f64),
+    /// The size of the screen in pixels
+    screen: (u32, u32),
+    /// Pixels per unit of mouse coordinates, more than 1 on HiDPI screens
+    scale: f64,
+}
+
+impl Camera {
+    /// A camera showing world coordinates as pixels, from the top left corner of a `width` by
+    /// `height` pixel screen with `scale` pixels per unit of mouse coordinates
+    pub fn new(width: u32, height: u32, scale: f64) -> Self {
+        Self {
+            zoom: 1.,
+            offset: (0., 0.),
+            screen: (width, height),
+            scale,
+        }
+    }
+
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom;
+    }
+
+    /// The size of the screen in pixels, which is what apps should lay out in
+    pub fn screen_size(&self) -> (u32, u32) {
+        self.screen
+    }
+
+    /// Pixels per unit of mouse coordinates, e.g. for making text as big on HiDPI screens
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Follow the screen changing size, keeping what is in the middle of it there
+    pub fn resize(&mut self, width: u32, height: u32, scale: f64) {
+        let (old_width, old_height) = self.screen;
+        let (centre_x, centre_y) = self.to_world(old_width as i32 / 2, old_height as i32 / 2);
+        self.screen = (width, height);
+        self.scale = scale;
+        self.offset = (
+            centre_x - (width / 2) as f64 / self.zoom,
+            centre_y - (height / 2) as f64 / self.zoom,
+        );
+    }
+
+    pub fn to_screen(&self, x: f64, y: f64) -> (i32, i32) {
+        (
+            ((x - self.offset.0) * self.zoom).floor() as i32,
+            ((y - self.offset.1) * self.zoom).floor() as i32,
+        )
+    }
+
+    pub fn to_world(&self, x: i32, y: i32) -> (f64, f64) {
+        (
+            x as f64 / self.zoom + self.offset.0,
+            y as f64 / self.zoom + self.offset.1,
+        )
+    }
+
+    /// The pixels covered by a world rectangle. Rectangles next to each other in the world are
+    /// next to each other on screen too, without gaps or overlaps, and are never less than a
+    /// pixel.
+    pub fn rect(&self, x: f64, y: f64, width: f64, height: f64) -> Rect {
+        let (left, top) = self.to_screen(x, y);
+        let (right, bottom) = self.to_screen(x + width, y + height);
+        Rect::new(
+            left,
+            top,
+            (right - left).max(1) as u32,
+            (bottom - top).max(1) as u32,
+        )
+    }
+
+    /// The pixels of the grid cell at `(row, col)`, with cells one world unit big
+    pub fn cell(&self, (row, col): (usize, usize)) -> Rect {
+        self.rect(col as f64, row as f64, 1., 1.)
+    }
+
+    pub fn point(&self, x: f64, y: f64) -> sdl2::rect::Point {
+        let (x, y) = self.to_screen(x, y);
+        sdl2::rect::Point::new(x, y)
+    }
+
+    /// Zoom by `factor`, keeping what is at the screen position `(x, y)` where it is
+    pub fn zoom_at(&mut self, factor: f64, x: i32, y: i32) {
+        let (world_x, world_y) = self.to_world(x, y);
+        self.zoom *= factor;
+        self.offset = (
+            world_x - x as f64 / self.zoom,
+            world_y - y as f64 / self.zoom,
+        );
+    }
+
+    /// Move what is shown by `(dx, dy)` pixels
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.offset.0 -= dx as f64 / self.zoom;
+        self.offset.1 -= dy as f64 / self.zoom;
+    }
+
+    /// Show all of `content` as big as possible, in the middle of the screen. The box counts as
+    /// cells, so it reaches one unit past its `max` corner.
+    pub fn fit(&mut self, content: Aabb) {
+        let (width, height) = (content.width() as f64, content.height() as f64);
+        let (screen_width, screen_height) = (self.screen.0 as f64, self.screen.1 as f64);
+        self.zoom = (screen_width / width).min(screen_height / height);
+        self.offset = (
+            content.min.x as f64 - (screen_width / self.zoom - width) / 2.,
+            content.min.y as f64 - (screen_height / self.zoom - height) / 2.,
+        );
+    }
+
+    /// Mouse coordinates are in points rather than pixels, which differ on HiDPI screens
+    fn mouse_to_screen(&self, coordinate: i32) -> i32 {
+        (coordinate as f64 * self.scale).round() as i32
+    }
+
+    /// Zoom with the mouse wheel, pan by dragging with the left button and fit `content` to the
+    /// screen with F. Returns whether the camera moved.
+    pub fn handle_event(
+        &mut self,
+        event: &Event,
+        mouse: MouseState,
+        content: Option<Aabb>,
+    ) -> bool {
+        match event {
+            Event::MouseWheel { y, .. } if *y != 0 => {
+                self.zoom_at(
+                    WHEEL_ZOOM.powi(*y),
+                    self.mouse_to_screen(mouse.x()),
+                    self.mouse_to_screen(mouse.y()),
+                );
+                true
+            }
+            Event::MouseMotion {
+                mousestate,
+                xrel,
+                yrel,
+                ..
+            } if mousestate.left() => {
+                self.pan(self.mouse_to_screen(*xrel), self.mouse_to_screen(*yrel));
+                true
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F),
+                ..
+            } => match content {
+                Some(content) => {
+                    self.fit(content);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+}
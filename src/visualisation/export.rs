@@ -0,0 +1,492 @@
+//! Writing the frames of a `WindowApp` to files, whether it runs in a window or headless
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use sdl2::{
+    image::SaveSurface,
+    pixels::PixelFormatEnum,
+    render::{Canvas, RenderTarget},
+    surface::Surface,
+};
+
+use super::{Frame, Output};
+
+/// Which frames to export, counting the frames an app draws from 0
+#[derive(Debug, Clone, Copy)]
+pub struct FrameRange {
+    pub first: usize,
+    /// The last frame to export, including it
+    pub last: Option<usize>,
+    /// Export every `step`th frame from `first` on
+    pub step: usize,
+}
+
+impl Default for FrameRange {
+    fn default() -> Self {
+        Self {
+            first: 0,
+            last: None,
+            step: 1,
+        }
+    }
+}
+
+impl FrameRange {
+    pub fn contains(&self, index: usize) -> bool {
+        index >= self.first
+            && self.last.is_none_or(|last| index <= last)
+            && (index - self.first).is_multiple_of(self.step.max(1))
+    }
+}
+
+/// Somewhere to write frames to
+pub trait FrameSink {
+    /// Write frame number `index`
+    fn write(&mut self, index: usize, frame: &Frame) -> Result<(), String>;
+
+    /// Called after the last frame
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Numbered PNG files in a directory, `frame_00000.png` and on
+pub struct PngSequence {
+    dir: PathBuf,
+}
+
+impl PngSequence {
+    pub fn new(dir: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        Ok(Self { dir })
+    }
+}
+
+impl FrameSink for PngSequence {
+    fn write(&mut self, index: usize, frame: &Frame) -> Result<(), String> {
+        let mut pixels = frame.pixels.clone();
+        let surface = Surface::from_data(
+            &mut pixels,
+            frame.width,
+            frame.height,
+            3 * frame.width,
+            PixelFormatEnum::RGB24,
+        )?;
+        surface.save(self.dir.join(format!("frame_{:05}.png", index)))
+    }
+}
+
+/// An animated GIF that loops forever. Every frame gets its own palette of up to 256 colours.
+pub struct Gif {
+    path: PathBuf,
+    /// Created on the first frame, as that is when the size is known
+    encoder: Option<gif::Encoder<BufWriter<File>>>,
+    /// How long each frame shows, in hundredths of a second
+    delay: u16,
+}
+
+impl Gif {
+    /// How hard the quantiser tries to find a good palette, from 1 (best) to 30 (fastest)
+    const QUANTISER_SPEED: i32 = 10;
+
+    pub fn new(path: PathBuf, delay: Duration) -> Self {
+        Self {
+            path,
+            encoder: None,
+            // Most viewers show anything below 2 hundredths as slow as 10
+            delay: ((delay.as_millis() / 10) as u16).max(2),
+        }
+    }
+
+    fn encoder(&mut self, frame: &Frame) -> Result<&mut gif::Encoder<BufWriter<File>>, String> {
+        if self.encoder.is_none() {
+            let file = File::create(&self.path)
+                .map_err(|e| format!("Could not create {}: {}", self.path.display(), e))?;
+            let mut encoder = gif::Encoder::new(
+                BufWriter::new(file),
+                frame.width as u16,
+                frame.height as u16,
+                &[],
+            )
+            .map_err(|e| e.to_string())?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(|e| e.to_string())?;
+            self.encoder = Some(encoder);
+        }
+        Ok(self.encoder.as_mut().unwrap())
+    }
+}
+
+impl FrameSink for Gif {
+    fn write(&mut self, _index: usize, frame: &Frame) -> Result<(), String> {
+        let mut gif_frame = gif::Frame::from_rgb_speed(
+            frame.width as u16,
+            frame.height as u16,
+            &frame.pixels,
+            Self::QUANTISER_SPEED,
+        );
+        gif_frame.delay = self.delay;
+        self.encoder(frame)?
+            .write_frame(&gif_frame)
+            .map_err(|e| e.to_string())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // Unlike dropping the encoder, this reports errors writing the end of the file
+        if let Some(encoder) = self.encoder.take() {
+            encoder.into_inner().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// An uncompressed YUV4MPEG2 video, which most video tools can read and encode. Colours are
+/// converted to full range YCbCr with the colour planes at half the resolution (4:2:0).
+pub struct Y4m {
+    out: Box<dyn Write>,
+    /// The frame rate as a fraction, frames per `fps.1` seconds
+    fps: (u32, u32),
+    /// The size of the video, from the first frame
+    size: Option<(u32, u32)>,
+}
+
+impl Y4m {
+    /// A video written to `path`, or to stdout if that is `-`
+    pub fn new(path: &Path, fps: (u32, u32)) -> Result<Self, String> {
+        let out: Box<dyn Write> = if path == Path::new("-") {
+            Box::new(BufWriter::new(io::stdout()))
+        } else {
+            Box::new(BufWriter::new(File::create(path).map_err(|e| {
+                format!("Could not create {}: {}", path.display(), e)
+            })?))
+        };
+        Ok(Self {
+            out,
+            fps,
+            size: None,
+        })
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        if self.size.is_none() {
+            writeln!(
+                self.out,
+                "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C420jpeg",
+                frame.width, frame.height, self.fps.0, self.fps.1
+            )?;
+            self.size = Some((frame.width, frame.height));
+        }
+        let (width, height) = (frame.width as usize, frame.height as usize);
+
+        let rgb = |x: usize, y: usize| {
+            let offset = 3 * (y * width + x);
+            let pixel = &frame.pixels[offset..offset + 3];
+            (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32)
+        };
+
+        let mut luma = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g, b) = rgb(x, y);
+                luma.push(to_byte(0.299 * r + 0.587 * g + 0.114 * b));
+            }
+        }
+
+        // Each colour sample is the average of a 2x2 block, or what there is of it at the edges
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let mut blue = Vec::with_capacity(chroma_width * chroma_height);
+        let mut red = Vec::with_capacity(chroma_width * chroma_height);
+        for y in (0..height).step_by(2) {
+            for x in (0..width).step_by(2) {
+                let block: Vec<_> = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]
+                    .into_iter()
+                    .filter(|(x, y)| *x < width && *y < height)
+                    .map(|(x, y)| rgb(x, y))
+                    .collect();
+                let count = block.len() as f32;
+                let (r, g, b) = block.iter().fold((0., 0., 0.), |sum, pixel| {
+                    (sum.0 + pixel.0, sum.1 + pixel.1, sum.2 + pixel.2)
+                });
+                let (r, g, b) = (r / count, g / count, b / count);
+                blue.push(to_byte(128. - 0.168736 * r - 0.331264 * g + 0.5 * b));
+                red.push(to_byte(128. + 0.5 * r - 0.418688 * g - 0.081312 * b));
+            }
+        }
+
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&luma)?;
+        self.out.write_all(&blue)?;
+        self.out.write_all(&red)
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    value.round().clamp(0., 255.) as u8
+}
+
+impl FrameSink for Y4m {
+    fn write(&mut self, _index: usize, frame: &Frame) -> Result<(), String> {
+        if self
+            .size
+            .is_some_and(|size| size != (frame.width, frame.height))
+        {
+            return Err("All frames of a video must be the same size".to_string());
+        }
+        self.write_frame(frame).map_err(|e| e.to_string())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// The frame rate exports play at when the app has no limit
+pub const EXPORT_FPS: u32 = 30;
+
+/// Passes the frames in `range` on to every sink the command line asked for
+pub struct Recorder {
+    range: FrameRange,
+    sinks: Vec<Box<dyn FrameSink>>,
+    downscale: u32,
+    /// The size of the first frame written. GIFs and videos can't change size, so when the
+    /// window or terminal is resized later frames are cropped or padded to it.
+    size: Option<(u32, u32)>,
+    /// The index of the next frame the app draws
+    next: usize,
+    written: usize,
+}
+
+impl Recorder {
+    /// Frames written to a GIF or a video show as long as they would at `fps` frames per second,
+    /// or `EXPORT_FPS` when there is no limit
+    pub fn new(output: &Output, fps: Option<u32>) -> Result<Self, String> {
+        // Skipped frames make the ones that are written show for longer
+        let (fps, step) = (
+            fps.unwrap_or(EXPORT_FPS).max(1),
+            output.frames.step.max(1) as u32,
+        );
+        let frame_time = Duration::from_secs(1) * step / fps;
+
+        let mut sinks: Vec<Box<dyn FrameSink>> = Vec::new();
+        if let Some(dir) = &output.export_frames {
+            sinks.push(Box::new(PngSequence::new(dir.clone())?));
+        }
+        if let Some(path) = &output.gif {
+            sinks.push(Box::new(Gif::new(
+                path.clone(),
+                output.gif_delay.unwrap_or(frame_time),
+            )));
+        }
+        if let Some(path) = &output.y4m {
+            sinks.push(Box::new(Y4m::new(path, (fps, step))?));
+        }
+
+        Ok(Self {
+            range: output.frames,
+            sinks,
+            downscale: output.downscale,
+            size: None,
+            next: 0,
+            written: 0,
+        })
+    }
+
+    /// Count a drawn frame and say whether it is one to write
+    fn take(&mut self) -> bool {
+        let index = self.next;
+        self.next += 1;
+        !self.sinks.is_empty() && self.range.contains(index)
+    }
+
+    fn write(&mut self, frame: &Frame) -> Result<(), String> {
+        let index = self.next - 1;
+        let (width, height) = *self.size.get_or_insert((frame.width, frame.height));
+        let frame = frame.centred(width, height).downscale(self.downscale);
+        for sink in &mut self.sinks {
+            sink.write(index, &frame)?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Record a frame that has been read already
+    pub fn record(&mut self, frame: &Frame) -> Result<(), String> {
+        if self.take() {
+            self.write(frame)?;
+        }
+        Ok(())
+    }
+
+    /// Record what has been drawn on `canvas`. Reading the pixels back is slow, so that only
+    /// happens for frames that get written.
+    pub fn record_canvas<T: RenderTarget>(&mut self, canvas: &Canvas<T>) -> Result<(), String> {
+        if self.take() {
+            self.write(&Frame::read(canvas)?)?;
+        }
+        Ok(())
+    }
+
+    /// Finish all sinks, returns how many frames were written
+    pub fn finish(mut self) -> Result<usize, String> {
+        for sink in &mut self.sinks {
+            sink.finish()?;
+        }
+        Ok(self.written)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(range: FrameRange) -> Vec<usize> {
+        (0..20).filter(|&index| range.contains(index)).collect()
+    }
+
+    #[test]
+    fn frame_range_defaults_to_every_frame() {
+        assert_eq!(frames(FrameRange::default()), (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn frame_range_includes_first_and_last() {
+        let range = FrameRange {
+            first: 3,
+            last: Some(6),
+            step: 1,
+        };
+        assert_eq!(frames(range), [3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn frame_range_steps_from_first() {
+        let range = FrameRange {
+            first: 2,
+            last: Some(12),
+            step: 5,
+        };
+        assert_eq!(frames(range), [2, 7, 12]);
+
+        let range = FrameRange {
+            first: 15,
+            last: None,
+            step: 2,
+        };
+        assert_eq!(frames(range), [15, 17, 19]);
+    }
+
+    #[test]
+    fn frame_range_takes_a_step_of_0_as_1() {
+        let range = FrameRange {
+            first: 18,
+            last: None,
+            step: 0,
+        };
+        assert_eq!(frames(range), [18, 19]);
+    }
+
+    fn filled(width: u32, height: u32, colour: [u8; 3]) -> Frame {
+        Frame {
+            width,
+            height,
+            pixels: colour.repeat((width * height) as usize),
+        }
+    }
+
+    /// Write `frames` to a video in the temporary directory and read it back
+    fn y4m(name: &str, frames: &[Frame]) -> (Vec<u8>, Result<(), String>) {
+        let path = std::env::temp_dir().join(format!("aoc21_{}_{}.y4m", name, std::process::id()));
+        let mut video = Y4m::new(&path, (30, 2)).unwrap();
+        let written = frames
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, frame)| video.write(index, frame))
+            .and_then(|()| video.finish());
+        drop(video);
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        (bytes, written)
+    }
+
+    #[test]
+    fn y4m_has_a_header_and_half_size_colour_planes() {
+        let (bytes, written) = y4m(
+            "planes",
+            &[filled(5, 3, [255, 255, 255]), filled(5, 3, [255, 0, 0])],
+        );
+        written.unwrap();
+
+        let header = b"YUV4MPEG2 W5 H3 F30:2 Ip A1:1 C420jpeg\n";
+        assert!(bytes.starts_with(header));
+        // 5 by 3 pixels of luma, and 3 by 2 of each colour for the odd sizes
+        let (luma, chroma) = (15, 6);
+        let frame_size = b"FRAME\n".len() + luma + 2 * chroma;
+        assert_eq!(bytes.len(), header.len() + 2 * frame_size);
+
+        let frames: Vec<_> = bytes[header.len()..].chunks(frame_size).collect();
+        for frame in &frames {
+            assert!(frame.starts_with(b"FRAME\n"));
+        }
+        let planes = |frame: &[u8]| {
+            let frame = &frame[b"FRAME\n".len()..];
+            let (luma, chroma) = frame.split_at(luma);
+            let (blue, red) = chroma.split_at(chroma.len() / 2);
+            (luma.to_vec(), blue.to_vec(), red.to_vec())
+        };
+        assert_eq!(
+            planes(frames[0]),
+            (vec![255; luma], vec![128; chroma], vec![128; chroma])
+        );
+        assert_eq!(
+            planes(frames[1]),
+            (vec![76; luma], vec![85; chroma], vec![255; chroma])
+        );
+    }
+
+    #[test]
+    fn y4m_frames_keep_their_size() {
+        let (bytes, written) = y4m("sizes", &[filled(4, 4, [0; 3]), filled(6, 4, [0; 3])]);
+        assert_eq!(
+            written,
+            Err("All frames of a video must be the same size".to_string())
+        );
+        // Only the first frame was written
+        let header = b"YUV4MPEG2 W4 H4 F30:2 Ip A1:1 C420jpeg\n";
+        assert!(bytes.starts_with(header));
+        assert_eq!(bytes.len(), header.len() + b"FRAME\n".len() + 16 + 2 * 4);
+    }
+
+    #[test]
+    fn recordings_keep_the_size_of_the_first_frame() {
+        let path = std::env::temp_dir().join(format!("aoc21_resized_{}.y4m", std::process::id()));
+        let output = Output {
+            y4m: Some(path.clone()),
+            ..Output::default()
+        };
+        let mut recorder = Recorder::new(&output, Some(30)).unwrap();
+        for (width, height) in [(4, 4), (6, 2), (2, 6)] {
+            recorder.record(&filled(width, height, [0; 3])).unwrap();
+        }
+        assert_eq!(recorder.finish(), Ok(3));
+
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let header = b"YUV4MPEG2 W4 H4 F30:1 Ip A1:1 C420jpeg\n";
+        assert!(bytes.starts_with(header));
+        assert_eq!(
+            bytes.len(),
+            header.len() + 3 * (b"FRAME\n".len() + 16 + 2 * 4)
+        );
+    }
+}
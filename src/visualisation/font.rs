@@ -0,0 +1,257 @@
+//! Fonts for text in visualisations: the configured TTF font, another one found on the system, or
+//! a small built-in bitmap font when there are none, so that text always shows
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use sdl2::{
+    pixels::Color,
+    rect::Rect,
+    render::{Canvas, RenderTarget},
+    ttf::{Font, FontStyle, Sdl2TtfContext},
+};
+
+/// Directories fonts are installed in on Linux, macOS and Windows, besides the user's own
+const FONT_DIRS: &[&str] = &[
+    "/usr/share/fonts",
+    "/usr/local/share/fonts",
+    "/Library/Fonts",
+    "/System/Library/Fonts",
+    "C:\\Windows\\Fonts",
+];
+
+/// Fonts to pick before any other, in order
+const PREFERRED_FONTS: &[&str] = &[
+    "DejaVuSans.ttf",
+    "LiberationSans-Regular.ttf",
+    "NotoSans-Regular.ttf",
+    "Ubuntu-R.ttf",
+    "Arial.ttf",
+    "arial.ttf",
+];
+
+/// How deep to look into font directories, which are sorted by family or foundry
+const SEARCH_DEPTH: usize = 4;
+
+fn font_files(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path.is_dir() {
+            if depth > 0 {
+                font_files(&path, depth - 1, files);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ttf"))
+        {
+            files.push(path);
+        }
+    }
+}
+
+/// `configured` if it exists, otherwise a TTF font installed on the system, preferring common
+/// sans-serif ones
+pub fn find(configured: &Path) -> Option<PathBuf> {
+    if configured.is_file() {
+        return Some(configured.to_path_buf());
+    }
+
+    let mut files = Vec::new();
+    let dirs = FONT_DIRS.iter().map(PathBuf::from).chain(dirs::font_dir());
+    for dir in dirs {
+        font_files(&dir, SEARCH_DEPTH, &mut files);
+    }
+    files.sort();
+
+    PREFERRED_FONTS
+        .iter()
+        .find_map(|name| {
+            files
+                .iter()
+                .find(|file| file.file_name().is_some_and(|file_name| file_name == *name))
+        })
+        .or_else(|| files.first())
+        .cloned()
+}
+
+/// A font to write text with
+pub enum TextFont<'ttf> {
+    Ttf(Font<'ttf, 'static>),
+    /// The built-in font, with every pixel of a glyph drawn as a `scale` by `scale` square
+    Bitmap {
+        scale: u32,
+    },
+}
+
+impl<'ttf> TextFont<'ttf> {
+    /// The font at `path` or one found instead, in bold at `size` points. Falls back to the bitmap
+    /// font when there is no TTF support or no font, saying why on stderr.
+    pub fn load(ttf: Option<&'ttf Sdl2TtfContext>, path: &Path, size: u16) -> Self {
+        let bitmap = TextFont::Bitmap {
+            scale: (size as u32 / 9).max(1),
+        };
+        let ttf = match ttf {
+            Some(ttf) => ttf,
+            None => {
+                eprintln!("TTF fonts are not available, using the built-in font");
+                return bitmap;
+            }
+        };
+        let found = match find(path) {
+            Some(found) => found,
+            None => {
+                eprintln!(
+                    "Could not find {} or any other TTF font, using the built-in font",
+                    path.display()
+                );
+                return bitmap;
+            }
+        };
+        if found != path {
+            eprintln!(
+                "Could not find {}, using {} instead",
+                path.display(),
+                found.display()
+            );
+        }
+
+        match ttf.load_font(&found, size) {
+            Ok(mut font) => {
+                font.set_style(FontStyle::BOLD);
+                TextFont::Ttf(font)
+            }
+            Err(e) => {
+                eprintln!(
+                    "Could not load {}: {}, using the built-in font",
+                    found.display(),
+                    e
+                );
+                bitmap
+            }
+        }
+    }
+}
+
+/// Width and height of a glyph of the bitmap font, in font pixels
+pub const GLYPH_WIDTH: u32 = 5;
+pub const GLYPH_HEIGHT: u32 = 7;
+
+/// Glyphs of the bitmap font, one row per byte from the top, the lowest 5 bits left to right.
+/// Lowercase letters are drawn as uppercase ones, and missing characters as `?`.
+#[rustfmt::skip]
+const GLYPHS: &[(char, [u8; 7])] = &[
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('"', [0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('#', [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('\'', [0b00100, 0b00100, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('*', [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b00110, 0b00100, 0b01000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    (';', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000]),
+    ('<', [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('>', [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('[', [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110]),
+    (']', [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110]),
+    ('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+];
+
+fn glyph(c: char) -> &'static [u8; 7] {
+    let c = c.to_ascii_uppercase();
+    GLYPHS
+        .iter()
+        .find(|(glyph, _)| *glyph == c)
+        .or_else(|| GLYPHS.iter().find(|(glyph, _)| *glyph == '?'))
+        .map(|(_, rows)| rows)
+        .unwrap()
+}
+
+/// The size of `text` in the bitmap font, with a font pixel of space between glyphs
+pub fn bitmap_text_size(text: &str, scale: u32) -> (u32, u32) {
+    let glyphs = text.chars().count() as u32;
+    (
+        scale * (glyphs * (GLYPH_WIDTH + 1)).saturating_sub(1),
+        scale * GLYPH_HEIGHT,
+    )
+}
+
+/// Draw `text` in the bitmap font in `color`, with its top left corner at `(x, y)` and every
+/// pixel of a glyph `scale` pixels big. `color` stays the canvas' draw colour afterwards.
+pub fn draw_bitmap_text<T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    scale: u32,
+    x: i32,
+    y: i32,
+    text: &str,
+    color: Color,
+) -> Result<(), String> {
+    let mut pixels = Vec::new();
+    for (idx, c) in text.chars().enumerate() {
+        let left = x + (idx as u32 * (GLYPH_WIDTH + 1) * scale) as i32;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                    pixels.push(Rect::new(
+                        left + (col * scale) as i32,
+                        y + (row as u32 * scale) as i32,
+                        scale,
+                        scale,
+                    ));
+                }
+            }
+        }
+    }
+
+    canvas.set_draw_color(color);
+    canvas.fill_rects(&pixels)
+}
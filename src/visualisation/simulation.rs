@@ -0,0 +1,160 @@
+//! Puzzles that change step by step, kept apart from how they are drawn. The same simulation can
+//! be shown in a window, in the terminal or headless by a `Renderer`, or run on its own in a
+//! test, and it runs at the same speed whatever the frame rate.
+
+use std::{any::Any, time::Duration};
+
+use sdl2::render::{Canvas, RenderTarget};
+
+use super::{camera::Camera, WindowApp};
+use crate::geometry::Aabb;
+
+/// Something that moves from an initial state towards a finished one, one step at a time
+pub trait Simulation {
+    /// Everything that changes from step to step
+    type State: Clone + 'static;
+
+    fn initial(&self) -> Self::State;
+    fn step(&self, state: &mut Self::State);
+    fn finished(&self, state: &Self::State) -> bool;
+
+    /// Step from the initial state until finished, or for at most `max_steps` steps. Returns the
+    /// last state and how many steps it took.
+    fn run(&self, max_steps: usize) -> (Self::State, usize) {
+        let mut state = self.initial();
+        let mut steps = 0;
+        while steps < max_steps && !self.finished(&state) {
+            self.step(&mut state);
+            steps += 1;
+        }
+        (state, steps)
+    }
+}
+
+/// Draws the states of a simulation `S`
+pub trait Renderer<S: Simulation> {
+    const WINDOW_NAME: &'static str;
+    /// The size the window opens at
+    const WINDOW_WIDTH: u32;
+    const WINDOW_HEIGHT: u32;
+    /// How often the window is redrawn, which doesn't change how fast the simulation runs
+    const WINDOW_FPS: Option<u32> = Some(60);
+    const SHOW_FPS: bool = true;
+    /// How fast the simulation runs
+    const STEPS_PER_SECOND: f64;
+
+    /// Draw `state` of `simulation` through `camera`, all of it, as the canvas may hold anything
+    fn draw<T: RenderTarget>(
+        &mut self,
+        simulation: &S,
+        state: &S::State,
+        canvas: &mut Canvas<T>,
+        camera: &Camera,
+    ) -> Result<(), String>;
+
+    /// The part of the world `simulation` is drawn in, for fitting it to the screen
+    fn content(&self, _simulation: &S) -> Option<Aabb> {
+        None
+    }
+
+    /// Pixels per world unit to start with, see `WindowApp::zoom`
+    fn zoom(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// The most time one update catches up on, so that the simulation doesn't jump ahead after the
+/// app was held up, e.g. while the window was being moved
+const MAX_CATCH_UP: Duration = Duration::from_millis(250);
+
+/// A simulation shown by a renderer, as a `WindowApp`
+pub struct Animation<S: Simulation, R: Renderer<S>> {
+    simulation: S,
+    renderer: R,
+    state: S::State,
+    /// Steps that are due but not taken yet, less than one between updates
+    pending_steps: f64,
+    /// The camera the finished state was drawn with, after which there is nothing new to show
+    finished_with: Option<Camera>,
+}
+
+impl<S: Simulation, R: Renderer<S>> Animation<S, R> {
+    pub fn new(simulation: S, renderer: R) -> Self {
+        Self {
+            state: simulation.initial(),
+            simulation,
+            renderer,
+            pending_steps: 0.,
+            finished_with: None,
+        }
+    }
+}
+
+impl<S: Simulation, R: Renderer<S>> WindowApp for Animation<S, R> {
+    const WINDOW_NAME: &'static str = R::WINDOW_NAME;
+    const WINDOW_WIDTH: u32 = R::WINDOW_WIDTH;
+    const WINDOW_HEIGHT: u32 = R::WINDOW_HEIGHT;
+    const WINDOW_FPS: Option<u32> = R::WINDOW_FPS;
+    const SHOW_FPS: bool = R::SHOW_FPS;
+
+    fn draw_frame<T: RenderTarget>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        camera: &Camera,
+    ) -> Result<bool, String> {
+        let finished = self.simulation.finished(&self.state);
+        if finished && self.finished_with == Some(*camera) {
+            return Ok(false);
+        }
+
+        self.renderer
+            .draw(&self.simulation, &self.state, canvas, camera)?;
+        self.finished_with = finished.then_some(*camera);
+        Ok(true)
+    }
+
+    fn update(&mut self, elapsed: Duration) -> bool {
+        self.pending_steps += elapsed.min(MAX_CATCH_UP).as_secs_f64() * R::STEPS_PER_SECOND;
+        let mut changed = false;
+        while self.pending_steps >= 1. && !self.simulation.finished(&self.state) {
+            self.simulation.step(&mut self.state);
+            self.pending_steps -= 1.;
+            changed = true;
+        }
+        self.pending_steps = self.pending_steps.min(1.);
+        changed
+    }
+
+    fn step(&mut self) -> bool {
+        if self.simulation.finished(&self.state) {
+            return false;
+        }
+        self.simulation.step(&mut self.state);
+        true
+    }
+
+    fn reset(&mut self) {
+        self.state = self.simulation.initial();
+        self.pending_steps = 0.;
+        self.finished_with = None;
+    }
+
+    fn content(&self) -> Option<Aabb> {
+        self.renderer.content(&self.simulation)
+    }
+
+    fn zoom(&self) -> Option<f64> {
+        self.renderer.zoom()
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Any>> {
+        Some(Box::new(self.state.clone()))
+    }
+
+    fn restore(&mut self, snapshot: &dyn Any) {
+        if let Some(state) = snapshot.downcast_ref::<S::State>() {
+            self.state = state.clone();
+            self.finished_with = None;
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Showing a `WindowApp` in the terminal, for when there is no display, e.g. over SSH. Frames are
+//! drawn offscreen with two pixels per character, one above the other, and shown as half blocks
+//! with the top pixel in front and the bottom one behind.
+
+use std::{
+    io::{self, BufWriter, Write},
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute, queue,
+    style::{self, Colors},
+    terminal::{self, ClearType},
+};
+use sdl2::pixels::Color;
+
+use super::{
+    camera::Camera, export::Recorder, faster, slower, Frame, Headless, Playback, WindowApp,
+};
+
+/// How much + and - zoom
+const KEY_ZOOM: f64 = 1.25;
+
+/// How far W, A, S and D pan, as a part of the screen
+const KEY_PAN: i32 = 8;
+
+/// How long to wait for a key while paused
+const PAUSED_POLL: Duration = Duration::from_millis(100);
+
+/// Raw mode on the alternate screen, so keys arrive as they are pressed and the shell's screen
+/// is left alone. The terminal is put back when this is dropped, also when the app panics.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> Result<Self, String> {
+        terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)
+            .map_err(|e| e.to_string())?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        execute!(
+            io::stdout(),
+            style::ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        )
+        .ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+/// The size of the offscreen canvas for a terminal of `columns` by `rows` characters, leaving
+/// the last row for a status line
+fn canvas_size((columns, rows): (u16, u16)) -> (u32, u32) {
+    (
+        columns.max(1) as u32,
+        2 * rows.saturating_sub(1).max(1) as u32,
+    )
+}
+
+/// Pixel sizes don't carry over from a window to a terminal, so apps start fitted to it
+fn initial_camera<A: WindowApp>(app: &A, width: u32, height: u32) -> Camera {
+    match app.content() {
+        Some(content) => {
+            let mut camera = Camera::new(width, height, 1.);
+            camera.fit(content);
+            camera
+        }
+        None => super::initial_camera(app, width, height, 1.),
+    }
+}
+
+/// Run `app` in the terminal until it is quit, with the same keys as in a window where there
+/// are any. The mouse is replaced by + and - to zoom and W, A, S and D to pan.
+pub fn run<A: WindowApp>(
+    app: &mut A,
+    mut target_fps: Option<u32>,
+    recorder: &mut Recorder,
+) -> Result<(), String> {
+    let _terminal = RawTerminal::enter()?;
+
+    let (width, height) = canvas_size(terminal::size().map_err(|e| e.to_string())?);
+    let mut headless = Headless::new(width, height)?;
+    let mut camera = initial_camera(app, width, height);
+    let mut playback = Playback::new();
+    let mut last_frame = Instant::now();
+    let mut frame_time = Duration::ZERO;
+
+    loop {
+        let wait = if playback.waiting() {
+            PAUSED_POLL
+        } else {
+            target_fps.map_or(Duration::ZERO, |fps| {
+                (last_frame + Duration::from_secs(1) / fps)
+                    .saturating_duration_since(Instant::now())
+            })
+        };
+
+        let mut camera_moved = false;
+        let handled = event::poll(wait).map_err(|e| e.to_string())?;
+        if handled {
+            // Handle everything that came in at once, e.g. a held down key
+            loop {
+                match event::read().map_err(|e| e.to_string())? {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('c'),
+                        modifiers: KeyModifiers::CONTROL,
+                    })
+                    | Event::Key(KeyEvent {
+                        code: KeyCode::Esc | KeyCode::Char('q'),
+                        ..
+                    }) => return Ok(()),
+                    Event::Key(KeyEvent { code, .. }) => match code {
+                        KeyCode::Char('r') => playback.reset(app),
+                        KeyCode::Char(' ') => playback.toggle_pause(),
+                        KeyCode::Right => playback.step(),
+                        KeyCode::Left => playback.rewind(app),
+                        KeyCode::Char('o') => target_fps = slower(target_fps),
+                        KeyCode::Char('p') => target_fps = faster(target_fps),
+                        code => camera_moved |= move_camera(&mut camera, code, app),
+                    },
+                    Event::Resize(columns, rows) => {
+                        let (width, height) = canvas_size((columns, rows));
+                        headless = Headless::new(width, height)?;
+                        camera.resize(width, height, 1.);
+                        execute!(io::stdout(), terminal::Clear(ClearType::All))
+                            .map_err(|e| e.to_string())?;
+                        camera_moved = true;
+                    }
+                    Event::Mouse(_) => (),
+                }
+                if !event::poll(Duration::ZERO).map_err(|e| e.to_string())? {
+                    break;
+                }
+            }
+        }
+
+        if camera_moved {
+            playback.redraw();
+        }
+        if handled {
+            // Pausing shows straight away, not with the next frame
+            let (width, height) = camera.screen_size();
+            show_status(width, height, &status::<A>(frame_time, playback.paused))
+                .map_err(|e| e.to_string())?;
+        }
+        // Keys don't make frames come sooner, but while paused they are all there is
+        let due = target_fps.is_none_or(|fps| last_frame.elapsed() >= Duration::from_secs(1) / fps);
+        if !(due || playback.paused) || !playback.next_frame(app) {
+            continue;
+        }
+
+        let now = Instant::now();
+        frame_time = now - last_frame;
+        last_frame = now;
+
+        if app.draw_frame(&mut headless.canvas, &camera)? {
+            let frame = headless.frame()?;
+            recorder.record(&frame)?;
+
+            show(&frame).map_err(|e| e.to_string())?;
+            show_status(
+                frame.width,
+                frame.height,
+                &status::<A>(frame_time, playback.paused),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+}
+
+/// Zoom, pan or fit for `code`, returns whether the camera moved
+fn move_camera<A: WindowApp>(camera: &mut Camera, code: KeyCode, app: &A) -> bool {
+    let (width, height) = camera.screen_size();
+    let (centre_x, centre_y) = (width as i32 / 2, height as i32 / 2);
+    let (pan_x, pan_y) = (width as i32 / KEY_PAN, height as i32 / KEY_PAN);
+    match code {
+        KeyCode::Char('+' | '=') => camera.zoom_at(KEY_ZOOM, centre_x, centre_y),
+        KeyCode::Char('-') => camera.zoom_at(1. / KEY_ZOOM, centre_x, centre_y),
+        // Moving the view one way moves the picture the other
+        KeyCode::Char('w') => camera.pan(0, pan_y),
+        KeyCode::Char('a') => camera.pan(pan_x, 0),
+        KeyCode::Char('s') => camera.pan(0, -pan_y),
+        KeyCode::Char('d') => camera.pan(-pan_x, 0),
+        KeyCode::Char('f') => match app.content() {
+            Some(content) => camera.fit(content),
+            None => return false,
+        },
+        _ => return false,
+    }
+    true
+}
+
+/// The app's name, how fast it runs and whether it is paused
+fn status<A: WindowApp>(frame_time: Duration, paused: bool) -> String {
+    let mut status = A::WINDOW_NAME.to_string();
+    if A::SHOW_FPS && !frame_time.is_zero() {
+        status += &format!(" - {:.0} fps", 1. / frame_time.as_secs_f32());
+    }
+    if paused {
+        status += " - paused";
+    }
+    status
+}
+
+/// Draw `frame` as half blocks from the top left corner
+fn show(frame: &Frame) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout());
+    // Colours are only sent when they change, which is most of the output otherwise
+    let mut colours = None;
+    for row in 0..frame.height / 2 {
+        queue!(out, cursor::MoveTo(0, row as u16))?;
+        for x in 0..frame.width {
+            let pair = (frame.pixel(x, 2 * row), frame.pixel(x, 2 * row + 1));
+            if colours != Some(pair) {
+                queue!(
+                    out,
+                    style::SetColors(Colors::new(to_term(pair.0), to_term(pair.1)))
+                )?;
+                colours = Some(pair);
+            }
+            out.write_all("▀".as_bytes())?;
+        }
+    }
+
+    queue!(out, style::ResetColor)?;
+    out.flush()
+}
+
+/// Write `status` on the line below a `width` by `height` pixel frame
+fn show_status(width: u32, height: u32, status: &str) -> io::Result<()> {
+    let status: String = status.chars().take(width as usize).collect();
+    let mut out = io::stdout();
+    queue!(
+        out,
+        cursor::MoveTo(0, (height / 2) as u16),
+        terminal::Clear(ClearType::CurrentLine),
+        style::Print(status)
+    )?;
+    out.flush()
+}
+
+fn to_term(colour: Color) -> style::Color {
+    style::Color::Rgb {
+        r: colour.r,
+        g: colour.g,
+        b: colour.b,
+    }
+}
@@ -0,0 +1,46 @@
+use std::process::{Command, Output};
+
+fn aoc21(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc21"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn bad_param_values_fail_the_run() {
+    for args in [
+        ["6", "-s", "-p", "days=abc"],
+        ["11", "-s", "-p", "steps=-1"],
+    ] {
+        let output = aoc21(&args);
+        assert!(!output.status.success(), "{:?} succeeded", args);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "", "{:?}", args);
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("Invalid value"),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn unknown_params_fail_the_run() {
+    for args in [&["6", "-s", "-p", "day=18"][..], &["-s", "-p", "day=18"]] {
+        let output = aoc21(args);
+        assert!(!output.status.success(), "{:?} succeeded", args);
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("Unknown parameter 'day'"),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn good_params_give_answers() {
+    let output = aoc21(&["6", "-s", "-p", "days=18"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("26 lanternfish"));
+}
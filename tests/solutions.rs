@@ -0,0 +1,148 @@
+use std::{collections::HashSet, fs};
+
+use aoc21::{
+    answers::Answers, config::Config, context::Context, day11, day7, day9, params::Params, parse,
+    registry::registry, util::Grid, visualisation::simulation::Simulation,
+};
+
+/// The names of all sample inputs for `day`, e.g. `12_sample_1`
+fn samples(config: &Config, year: u16, day: u8) -> Vec<String> {
+    let prefix = format!("{}_sample", day);
+    let mut samples: Vec<String> = fs::read_dir(config.year_dir(year))
+        .map(|entries| {
+            entries
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .filter(|name| name == &prefix || name.starts_with(&format!("{}_", prefix)))
+                .collect()
+        })
+        .unwrap_or_default();
+    samples.sort();
+    samples
+}
+
+#[test]
+fn samples_give_known_answers() {
+    let config = Config::default();
+    let mut checked = 0;
+
+    for day in registry() {
+        let answers = Answers::load(&config, day.year).unwrap();
+        for sample in samples(&config, day.year, day.day) {
+            let input = fs::read_to_string(config.year_file(day.year, &sample)).unwrap();
+            for implementation in &day.implementations {
+                let expected = match answers.get(&sample, implementation.part) {
+                    Some(expected) => expected,
+                    None => continue,
+                };
+                assert_eq!(
+                    (implementation.solve)(&input, &Context::default()).as_deref(),
+                    Ok(expected),
+                    "{} of day {} part {} on {}",
+                    implementation.name,
+                    day.day,
+                    implementation.part,
+                    sample
+                );
+                checked += 1;
+            }
+        }
+    }
+
+    assert!(checked > 0, "No sample has a known answer");
+}
+
+#[test]
+fn every_day_has_both_parts() {
+    for day in registry() {
+        let parts: Vec<u8> = day.parts().map(|i| i.part).collect();
+        assert_eq!(parts, [1, 2], "Parts of day {} of {}", day.day, day.year);
+    }
+}
+
+#[test]
+fn solutions_claim_their_own_part() {
+    for day in registry() {
+        for implementation in &day.implementations {
+            // Implementations are called `Part1`, `Part2Flood`, ... after the part they solve
+            assert!(
+                implementation
+                    .name
+                    .starts_with(&format!("Part{}", implementation.part)),
+                "{} of day {} of {} claims to be part {}",
+                implementation.name,
+                day.day,
+                day.year,
+                implementation.part
+            );
+        }
+    }
+}
+
+#[test]
+fn nothing_is_registered_twice() {
+    let mut days = HashSet::new();
+    let mut implementations = HashSet::new();
+    for day in registry() {
+        assert!(
+            days.insert((day.year, day.day)),
+            "Day {} of {} is registered twice",
+            day.day,
+            day.year
+        );
+        for implementation in &day.implementations {
+            assert!(
+                implementations.insert((
+                    day.year,
+                    day.day,
+                    implementation.part,
+                    implementation.name
+                )),
+                "{} of day {} of {} is registered twice",
+                implementation.name,
+                day.day,
+                day.year
+            );
+        }
+    }
+}
+
+/// The simulations behind the visualisations end where the solutions say they should
+#[test]
+fn simulations_finish_on_samples() {
+    let config = Config::default();
+    let sample = |day: u8| fs::read_to_string(config.year_file(2021, &format!("{}_sample_1", day)));
+
+    let crabs = day7::Crabs::new(parse::comma_separated(&sample(7).unwrap()).unwrap());
+    let (positions, steps) = crabs.run(1000);
+    assert!(positions.iter().all(|x| *x == crabs.target_position()));
+    // The crab at 16 is the furthest from 2
+    assert_eq!(steps, 14);
+
+    let basins = day9::Basins::new(Grid::from_digits(&sample(9).unwrap()).unwrap());
+    let (mapping, _) = basins.run(1000);
+    let mapped = mapping.mapped.iter().filter(|(_, mapped)| **mapped).count();
+    assert_eq!(mapped, 3 + 9 + 14 + 9);
+
+    let octopuses = day11::Octopuses::new(Grid::from_digits(&sample(11).unwrap()).unwrap());
+    assert_eq!(octopuses.run(1000).1, 195);
+}
+
+/// Parameters that can't be used fail the solution rather than give an answer
+#[test]
+fn bad_params_fail() {
+    let config = Config::default();
+    for (day, param) in [(6, "days=abc"), (6, "days=-1"), (11, "steps=-1")] {
+        let ctx = Context::new(None, Params::parse([param]).unwrap());
+        let input = fs::read_to_string(config.input_path(2021, day, Some("1"))).unwrap();
+        let day = registry().into_iter().find(|d| d.day == day).unwrap();
+        for implementation in day.parts().filter(|i| !i.params.is_empty()) {
+            assert!(
+                (implementation.solve)(&input, &ctx).is_err(),
+                "{} of day {} with {}",
+                implementation.name,
+                day.day,
+                param
+            );
+        }
+    }
+}